@@ -0,0 +1,110 @@
+//! Загрузка записей по списку ID любой длины: список делится на части по
+//! максимальному `limit` API, части запрашиваются параллельно (в пределах
+//! общего лимита запросов), результат идёт в порядке входного списка.
+
+use std::collections::HashMap;
+use std::future::Future;
+
+use serde::Serialize;
+
+use crate::{paging, ApiError};
+
+/// Запись для одного запрошенного ID.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ByIdEntry<T> {
+    Found { id: i64, item: T },
+    /// API не вернул запись с таким ID: её нет или она скрыта.
+    Missing { id: i64 },
+}
+
+/// Запрашивает записи по `ids` частями через `fetch` и раскладывает их по
+/// порядку `ids`. Повторяющиеся ID запрашиваются один раз.
+pub async fn lookup<T, F, Fut>(ids: Vec<i64>, fetch: F, id_of: fn(&T) -> i64) -> Result<Vec<ByIdEntry<T>>, ApiError>
+where
+    T: Clone + Send + 'static,
+    F: Fn(Vec<i64>) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, ApiError>> + Send + 'static,
+{
+    let mut unique = ids.clone();
+    unique.sort_unstable();
+    unique.dedup();
+
+    let tasks: Vec<_> = unique
+        .chunks(paging::API_MAX_LIMIT as usize)
+        .map(|chunk| tauri::async_runtime::spawn(fetch(chunk.to_vec())))
+        .collect();
+
+    let mut found = HashMap::new();
+    for task in tasks {
+        let items = task.await.map_err(ApiError::task_failed)??;
+        found.extend(items.into_iter().map(|item| (id_of(&item), item)));
+    }
+
+    Ok(ids
+        .into_iter()
+        .map(|id| match found.get(&id) {
+            Some(item) => ByIdEntry::Found { id, item: item.clone() },
+            None => ByIdEntry::Missing { id },
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn statuses(entries: &[ByIdEntry<i64>]) -> Vec<(i64, bool)> {
+        entries
+            .iter()
+            .map(|e| match e {
+                ByIdEntry::Found { id, item } => {
+                    assert_eq!(id, item);
+                    (*id, true)
+                }
+                ByIdEntry::Missing { id } => (*id, false),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn entries_follow_input_order_and_mark_missing() {
+        // API отдаёт записи в своём порядке и без ID, кратных 10
+        let fetch = |chunk: Vec<i64>| async move { Ok::<_, ApiError>(chunk.into_iter().rev().filter(|id| id % 10 != 0).collect::<Vec<i64>>()) };
+        let entries = tauri::async_runtime::block_on(lookup(vec![30, 3, 10, 3, 1], fetch, |id: &i64| *id)).unwrap();
+        assert_eq!(statuses(&entries), vec![(30, false), (3, true), (10, false), (3, true), (1, true)]);
+    }
+
+    #[test]
+    fn long_lists_are_split_into_api_sized_chunks() {
+        let chunks = Arc::new(Mutex::new(Vec::new()));
+        let fetch = |chunk: Vec<i64>| {
+            let chunks = chunks.clone();
+            async move {
+                chunks.lock().unwrap().push(chunk.len());
+                Ok::<_, ApiError>(chunk)
+            }
+        };
+        let ids: Vec<i64> = (1..=120).rev().collect();
+        let entries = tauri::async_runtime::block_on(lookup(ids.clone(), fetch, |id: &i64| *id)).unwrap();
+
+        let mut sizes = chunks.lock().unwrap().clone();
+        sizes.sort_unstable();
+        assert_eq!(sizes, vec![20, 50, 50]);
+        assert_eq!(statuses(&entries).into_iter().map(|(id, _)| id).collect::<Vec<_>>(), ids);
+    }
+
+    #[test]
+    fn failed_chunk_fails_lookup() {
+        let fetch = |chunk: Vec<i64>| async move {
+            if chunk.contains(&60) {
+                Err(ApiError::validation("boom".to_string()))
+            } else {
+                Ok(chunk)
+            }
+        };
+        let result = tauri::async_runtime::block_on(lookup((1..=60).collect(), fetch, |id: &i64| *id));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,482 @@
+//! Разбор BBCode из поля `description` Shikimori в структурированное дерево,
+//! чтобы фронтенд мог рисовать ссылки на сущности как навигацию внутри приложения.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    Anime,
+    Manga,
+    Ranobe,
+    Character,
+    Person,
+}
+
+impl EntityKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Anime => "anime",
+            Self::Manga => "manga",
+            Self::Ranobe => "ranobe",
+            Self::Character => "character",
+            Self::Person => "person",
+        }
+    }
+
+    /// Адрес для навигации внутри приложения, например `shikimore://anime/5`.
+    pub fn app_url(self, id: i64) -> String {
+        format!("shikimore://{}/{}", self.as_str(), id)
+    }
+
+    fn from_tag(name: &str) -> Option<Self> {
+        match name {
+            "anime" => Some(Self::Anime),
+            "manga" => Some(Self::Manga),
+            "ranobe" => Some(Self::Ranobe),
+            "character" => Some(Self::Character),
+            "person" | "people" => Some(Self::Person),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TextStyle {
+    Bold,
+    Italic,
+    Underline,
+    Strike,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RichNode {
+    Text { text: String },
+    LineBreak,
+    Format { style: TextStyle, children: Vec<RichNode> },
+    /// Ссылка на сущность Shikimori. `text` пуст у самозакрывающихся тегов вида `[anime=5]`.
+    EntityLink { entity: EntityKind, id: i64, text: Option<String> },
+    Url { href: String, children: Vec<RichNode> },
+    Spoiler { label: Option<String>, children: Vec<RichNode> },
+    Quote { author: Option<String>, children: Vec<RichNode> },
+    List { items: Vec<Vec<RichNode>> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Text(&'a str),
+    Newline,
+    Open { name: &'a str, arg: Option<&'a str>, raw: &'a str },
+    Close { name: &'a str, raw: &'a str },
+}
+
+// Теги, которые мы понимаем. Всё остальное в квадратных скобках остаётся текстом:
+// в описаниях полно конструкций вроде "[TV-1]" или "[18+]".
+const KNOWN_TAGS: &[&str] = &[
+    "b", "i", "u", "s", "url", "spoiler", "spoiler_block", "quote", "list", "*",
+    "anime", "manga", "ranobe", "character", "person", "people",
+    "center", "right", "left", "size", "color", "div", "span", "p", "h2", "h3", "h4",
+    "br", "hr", "img", "poster", "image", "source",
+];
+
+fn tokenize(input: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        let next = rest.find(['[', '\n']).unwrap_or(rest.len());
+        if next > 0 {
+            tokens.push(Token::Text(&rest[..next]));
+            rest = &rest[next..];
+            continue;
+        }
+
+        if rest.starts_with('\n') {
+            tokens.push(Token::Newline);
+            rest = &rest[1..];
+            continue;
+        }
+
+        match parse_tag(rest) {
+            Some((token, len)) => {
+                tokens.push(token);
+                rest = &rest[len..];
+            }
+            None => {
+                tokens.push(Token::Text("["));
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    tokens
+}
+
+fn parse_tag(input: &str) -> Option<(Token<'_>, usize)> {
+    let end = input.find(']')?;
+    let inner = &input[1..end];
+    if inner.contains(['[', '\n']) {
+        return None;
+    }
+
+    let raw = &input[..end + 1];
+    let token = if let Some(name) = inner.strip_prefix('/') {
+        Token::Close { name, raw }
+    } else {
+        let (name, arg) = match inner.split_once('=') {
+            Some((name, arg)) => (name, Some(arg.trim())),
+            None => (inner, None),
+        };
+        Token::Open { name, arg, raw }
+    };
+
+    let name = match token {
+        Token::Open { name, .. } | Token::Close { name, .. } => name,
+        _ => unreachable!(),
+    };
+    if !KNOWN_TAGS.iter().any(|t| t.eq_ignore_ascii_case(name)) {
+        return None;
+    }
+
+    Some((token, end + 1))
+}
+
+#[derive(Debug)]
+enum FrameKind {
+    Root,
+    Format(TextStyle),
+    Url(Option<String>),
+    Entity(EntityKind, i64),
+    Spoiler(Option<String>),
+    Quote(Option<String>),
+    List { items: Vec<Vec<RichNode>>, implicit: bool },
+    Transparent,
+    Dropped,
+}
+
+#[derive(Debug)]
+struct Frame {
+    tag: String,
+    kind: FrameKind,
+    children: Vec<RichNode>,
+}
+
+impl Frame {
+    fn new(tag: &str, kind: FrameKind) -> Self {
+        Self { tag: tag.to_string(), kind, children: Vec::new() }
+    }
+
+    fn push(&mut self, node: RichNode) {
+        if let (Some(RichNode::Text { text: prev }), RichNode::Text { text }) = (self.children.last_mut(), &node) {
+            prev.push_str(text);
+            return;
+        }
+        self.children.push(node);
+    }
+
+    fn start_list_item(&mut self) {
+        if let FrameKind::List { items, .. } = &mut self.kind {
+            let item = std::mem::take(&mut self.children);
+            if !is_blank(&item) {
+                items.push(trim_nodes(item));
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<RichNode> {
+        self.start_list_item();
+        let children = self.children;
+        match self.kind {
+            FrameKind::Root | FrameKind::Transparent => children,
+            FrameKind::Dropped => Vec::new(),
+            FrameKind::Format(style) => vec![RichNode::Format { style, children }],
+            FrameKind::Entity(entity, id) => {
+                let text = plain_text(&children);
+                let text = text.trim();
+                vec![RichNode::EntityLink {
+                    entity,
+                    id,
+                    text: if text.is_empty() { None } else { Some(text.to_string()) },
+                }]
+            }
+            FrameKind::Url(href) => {
+                let href = href.unwrap_or_else(|| plain_text(&children).trim().to_string());
+                if is_safe_href(&href) {
+                    vec![RichNode::Url { href, children }]
+                } else {
+                    children
+                }
+            }
+            FrameKind::Spoiler(label) => vec![RichNode::Spoiler { label, children }],
+            FrameKind::Quote(author) => vec![RichNode::Quote { author, children }],
+            FrameKind::List { items, .. } => vec![RichNode::List { items }],
+        }
+    }
+}
+
+fn is_safe_href(href: &str) -> bool {
+    href.starts_with("http://") || href.starts_with("https://") || href.starts_with('/')
+}
+
+fn is_blank(nodes: &[RichNode]) -> bool {
+    nodes.iter().all(|n| match n {
+        RichNode::Text { text } => text.trim().is_empty(),
+        RichNode::LineBreak => true,
+        _ => false,
+    })
+}
+
+fn trim_nodes(mut nodes: Vec<RichNode>) -> Vec<RichNode> {
+    while matches!(nodes.last(), Some(RichNode::LineBreak)) {
+        nodes.pop();
+    }
+    if let Some(RichNode::Text { text }) = nodes.first_mut() {
+        *text = text.trim_start().to_string();
+    }
+    if let Some(RichNode::Text { text }) = nodes.last_mut() {
+        *text = text.trim_end().to_string();
+    }
+    nodes.retain(|n| !matches!(n, RichNode::Text { text } if text.is_empty()));
+    nodes
+}
+
+/// Склеивает текстовое содержимое узлов, отбрасывая разметку.
+pub fn plain_text(nodes: &[RichNode]) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        match node {
+            RichNode::Text { text } => out.push_str(text),
+            RichNode::LineBreak => out.push('\n'),
+            RichNode::EntityLink { text, .. } => out.push_str(text.as_deref().unwrap_or_default()),
+            RichNode::Format { children, .. }
+            | RichNode::Url { children, .. }
+            | RichNode::Spoiler { children, .. }
+            | RichNode::Quote { children, .. } => out.push_str(&plain_text(children)),
+            RichNode::List { items } => {
+                for item in items {
+                    out.push_str(&plain_text(item));
+                    out.push('\n');
+                }
+            }
+        }
+    }
+    out
+}
+
+// Автор цитаты бывает в виде "c123;456;nickname" (комментарий;пользователь;ник).
+fn quote_author(arg: Option<&str>) -> Option<String> {
+    let author = arg?.rsplit(';').next()?.trim();
+    if author.is_empty() { None } else { Some(author.to_string()) }
+}
+
+/// Есть ли дальше закрывающий тег раньше, чем следующий открывающий с тем же именем.
+fn has_closing(tokens: &[Token<'_>], name: &str) -> bool {
+    for token in tokens {
+        match token {
+            Token::Close { name: n, .. } if n.eq_ignore_ascii_case(name) => return true,
+            Token::Open { name: n, .. } if n.eq_ignore_ascii_case(name) => return false,
+            _ => {}
+        }
+    }
+    false
+}
+
+fn close_until(stack: &mut Vec<Frame>, depth: usize) {
+    while stack.len() > depth {
+        let frame = stack.pop().unwrap();
+        let nodes = frame.finish();
+        let parent = stack.last_mut().unwrap();
+        for node in nodes {
+            parent.push(node);
+        }
+    }
+}
+
+/// Разбирает BBCode-описание Shikimori. Незакрытые теги закрываются в конце текста,
+/// непарные закрывающие теги остаются обычным текстом.
+pub fn parse(input: &str) -> Vec<RichNode> {
+    let input = input.replace("\r\n", "\n");
+    let tokens = tokenize(&input);
+    let mut stack = vec![Frame::new("", FrameKind::Root)];
+
+    for (i, token) in tokens.iter().enumerate() {
+        match *token {
+            Token::Text(text) => stack.last_mut().unwrap().push(RichNode::Text { text: text.to_string() }),
+            Token::Newline => {
+                let top = stack.last().unwrap();
+                if let FrameKind::List { implicit: true, .. } = top.kind {
+                    let next_is_item = tokens[i + 1..]
+                        .iter()
+                        .find(|t| !matches!(t, Token::Text(s) if s.trim().is_empty()))
+                        .is_some_and(|t| matches!(t, Token::Open { name: "*", .. }));
+                    if next_is_item {
+                        continue;
+                    }
+                    let depth = stack.len() - 1;
+                    close_until(&mut stack, depth);
+                }
+                stack.last_mut().unwrap().push(RichNode::LineBreak);
+            }
+            Token::Open { name, arg, .. } => {
+                let tag = name.to_ascii_lowercase();
+                if tag == "*" {
+                    match stack.iter().rposition(|f| matches!(f.kind, FrameKind::List { .. })) {
+                        Some(pos) => {
+                            close_until(&mut stack, pos + 1);
+                            stack[pos].start_list_item();
+                        }
+                        None => stack.push(Frame::new("", FrameKind::List { items: Vec::new(), implicit: true })),
+                    }
+                    continue;
+                }
+
+                if tag == "br" || tag == "hr" {
+                    stack.last_mut().unwrap().push(RichNode::LineBreak);
+                    continue;
+                }
+
+                let kind = match tag.as_str() {
+                    "b" => FrameKind::Format(TextStyle::Bold),
+                    "i" => FrameKind::Format(TextStyle::Italic),
+                    "u" => FrameKind::Format(TextStyle::Underline),
+                    "s" => FrameKind::Format(TextStyle::Strike),
+                    "url" => FrameKind::Url(arg.map(str::to_string)),
+                    "spoiler" | "spoiler_block" => {
+                        FrameKind::Spoiler(arg.filter(|a| !a.is_empty()).map(str::to_string))
+                    }
+                    "quote" => FrameKind::Quote(quote_author(arg)),
+                    "list" => FrameKind::List { items: Vec::new(), implicit: false },
+                    "img" | "poster" | "source" => FrameKind::Dropped,
+                    // [image=123] — ссылка на загруженную картинку, отдельного содержимого нет
+                    "image" => continue,
+                    _ => match (EntityKind::from_tag(&tag), arg.and_then(|a| a.parse::<i64>().ok())) {
+                        (Some(entity), Some(id)) => {
+                            if !has_closing(&tokens[i + 1..], &tag) {
+                                stack.last_mut().unwrap().push(RichNode::EntityLink { entity, id, text: None });
+                                continue;
+                            }
+                            FrameKind::Entity(entity, id)
+                        }
+                        _ => FrameKind::Transparent,
+                    },
+                };
+                stack.push(Frame::new(&tag, kind));
+            }
+            Token::Close { name, raw } => {
+                let tag = name.to_ascii_lowercase();
+                match stack.iter().skip(1).rposition(|f| f.tag == tag) {
+                    Some(pos) => close_until(&mut stack, pos + 1),
+                    None => stack.last_mut().unwrap().push(RichNode::Text { text: raw.to_string() }),
+                }
+            }
+        }
+    }
+
+    close_until(&mut stack, 1);
+    stack.pop().unwrap().finish()
+}
+
+fn is_spoiler_tag(name: &str) -> bool {
+    name.eq_ignore_ascii_case("spoiler") || name.eq_ignore_ascii_case("spoiler_block")
+}
+
+/// Переписывает исходный BBCode, убирая теги `[spoiler]`. При `keep_content == false`
+/// содержимое спойлеров вырезается целиком (незакрытый спойлер — до конца текста),
+/// иначе остаётся как обычный текст.
+pub fn rewrite_spoilers(input: &str, keep_content: bool) -> String {
+    let tokens = tokenize(input);
+    let mut out = String::with_capacity(input.len());
+    let mut depth = 0usize;
+
+    for token in &tokens {
+        match *token {
+            Token::Open { name, .. } if is_spoiler_tag(name) => depth += 1,
+            Token::Close { name, .. } if is_spoiler_tag(name) && depth > 0 => depth -= 1,
+            _ if depth > 0 && !keep_content => {}
+            Token::Text(text) => out.push_str(text),
+            Token::Newline => out.push('\n'),
+            Token::Open { raw, .. } | Token::Close { raw, .. } => out.push_str(raw),
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str) -> RichNode {
+        RichNode::Text { text: text.to_string() }
+    }
+
+    #[test]
+    fn nested_tags() {
+        assert_eq!(
+            parse("[b]bold [i]both[/i][/b]"),
+            vec![RichNode::Format {
+                style: TextStyle::Bold,
+                children: vec![text("bold "), RichNode::Format { style: TextStyle::Italic, children: vec![text("both")] }],
+            }]
+        );
+    }
+
+    #[test]
+    fn unclosed_tag_closes_at_end() {
+        assert_eq!(parse("[b]bold"), vec![RichNode::Format { style: TextStyle::Bold, children: vec![text("bold")] }]);
+    }
+
+    #[test]
+    fn outer_close_closes_inner_tags() {
+        assert_eq!(
+            parse("[b]a[i]b[/b]c[/i]"),
+            vec![
+                RichNode::Format {
+                    style: TextStyle::Bold,
+                    children: vec![text("a"), RichNode::Format { style: TextStyle::Italic, children: vec![text("b")] }],
+                },
+                text("c[/i]"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unmatched_close_stays_text() {
+        assert_eq!(parse("a[/b] [TV-1]"), vec![text("a[/b] [TV-1]")]);
+    }
+
+    #[test]
+    fn entity_without_closing_tag() {
+        assert_eq!(
+            parse("[anime=5] и [character=7]Имя[/character]"),
+            vec![
+                RichNode::EntityLink { entity: EntityKind::Anime, id: 5, text: None },
+                text(" и "),
+                RichNode::EntityLink { entity: EntityKind::Character, id: 7, text: Some("Имя".to_string()) },
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_spoilers() {
+        assert_eq!(
+            parse("[spoiler=Метка]x [spoiler]y[/spoiler][/spoiler]"),
+            vec![RichNode::Spoiler {
+                label: Some("Метка".to_string()),
+                children: vec![text("x "), RichNode::Spoiler { label: None, children: vec![text("y")] }],
+            }]
+        );
+        let input = "[spoiler=a]x [spoiler]y[/spoiler] z[/spoiler] конец";
+        assert_eq!(rewrite_spoilers(input, false), " конец");
+        assert_eq!(rewrite_spoilers(input, true), "x y z конец");
+    }
+
+    #[test]
+    fn unclosed_spoiler_runs_to_end() {
+        assert_eq!(rewrite_spoilers("до [spoiler]секрет", false), "до ");
+        assert_eq!(rewrite_spoilers("до [spoiler]секрет", true), "до секрет");
+        assert_eq!(parse("до [spoiler]секрет"), vec![text("до "), RichNode::Spoiler { label: None, children: vec![text("секрет")] }]);
+    }
+}
@@ -0,0 +1,268 @@
+//! Ошибка, которую возвращают все команды. `kind` — широкая категория,
+//! `code` — стабильный код конкретной причины, `message` — текст на языке
+//! интерфейса, `source` — цепочка исходных ошибок для отладки.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use shikicrate::ShikicrateError;
+
+use crate::i18n::{t, tf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    Validation,
+    Http,
+    Graphql,
+    RateLimit,
+    Api,
+    Serialization,
+    NotFound,
+    Internal,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InvalidParameter,
+    Network,
+    GraphqlError,
+    RateLimited,
+    HttpStatus,
+    InvalidResponse,
+    AnimeNotFound,
+    MangaNotFound,
+    CharacterNotFound,
+    ProfileNotFound,
+    ImageFetch,
+    ImageDecode,
+    Storage,
+    TaskFailed,
+    Channel,
+    Cancelled,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidParameter => "invalid_parameter",
+            ErrorCode::Network => "network",
+            ErrorCode::GraphqlError => "graphql_error",
+            ErrorCode::RateLimited => "rate_limited",
+            ErrorCode::HttpStatus => "http_status",
+            ErrorCode::InvalidResponse => "invalid_response",
+            ErrorCode::AnimeNotFound => "anime_not_found",
+            ErrorCode::MangaNotFound => "manga_not_found",
+            ErrorCode::CharacterNotFound => "character_not_found",
+            ErrorCode::ProfileNotFound => "profile_not_found",
+            ErrorCode::ImageFetch => "image_fetch",
+            ErrorCode::ImageDecode => "image_decode",
+            ErrorCode::Storage => "storage",
+            ErrorCode::TaskFailed => "task_failed",
+            ErrorCode::Channel => "channel",
+            ErrorCode::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn kind(self) -> ErrorKind {
+        match self {
+            ErrorCode::InvalidParameter => ErrorKind::Validation,
+            ErrorCode::Network | ErrorCode::ImageFetch => ErrorKind::Http,
+            ErrorCode::GraphqlError => ErrorKind::Graphql,
+            ErrorCode::RateLimited => ErrorKind::RateLimit,
+            ErrorCode::HttpStatus => ErrorKind::Api,
+            ErrorCode::InvalidResponse | ErrorCode::ImageDecode => ErrorKind::Serialization,
+            ErrorCode::AnimeNotFound
+            | ErrorCode::MangaNotFound
+            | ErrorCode::CharacterNotFound
+            | ErrorCode::ProfileNotFound => ErrorKind::NotFound,
+            ErrorCode::Storage | ErrorCode::TaskFailed | ErrorCode::Channel => ErrorKind::Internal,
+            ErrorCode::Cancelled => ErrorKind::Cancelled,
+        }
+    }
+
+    /// Имеет ли смысл повторить запрос без изменений.
+    fn retryable(self) -> bool {
+        matches!(self, ErrorCode::Network | ErrorCode::RateLimited | ErrorCode::ImageFetch)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiError {
+    pub kind: ErrorKind,
+    pub code: ErrorCode,
+    pub message: String,
+    /// HTTP-статус ответа, если ошибку вернул сервер.
+    pub status: Option<u16>,
+    pub retryable: bool,
+    pub retry_after: Option<u64>,
+    /// Исходные ошибки, от ближайшей к самой глубокой.
+    #[serde(default)]
+    pub source: Vec<String>,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code.as_str(), self.message)?;
+        for source in &self.source {
+            write!(f, " <- {}", source)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: String) -> Self {
+        ApiError {
+            kind: code.kind(),
+            code,
+            message,
+            status: None,
+            retryable: code.retryable(),
+            retry_after: None,
+            source: Vec::new(),
+        }
+    }
+
+    pub fn with_status(mut self, status: u16) -> Self {
+        self.status = Some(status);
+        self.retryable = self.retryable || status == 429 || status >= 500;
+        self
+    }
+
+    pub fn caused_by(mut self, source: impl fmt::Display) -> Self {
+        self.source.push(source.to_string());
+        self
+    }
+
+    /// Некорректный параметр команды; `message` уже на языке интерфейса.
+    pub fn validation(message: String) -> Self {
+        ApiError::new(ErrorCode::InvalidParameter, message)
+    }
+
+    pub fn anime_not_found(id: i64) -> Self {
+        ApiError::new(ErrorCode::AnimeNotFound, tf("error.anime_not_found", &[("id", &id)]))
+    }
+
+    pub fn manga_not_found(id: i64) -> Self {
+        ApiError::new(ErrorCode::MangaNotFound, tf("error.manga_not_found", &[("id", &id)]))
+    }
+
+    pub fn character_not_found(id: i64) -> Self {
+        ApiError::new(ErrorCode::CharacterNotFound, tf("error.character_not_found", &[("id", &id)]))
+    }
+
+    pub fn profile_not_found(name: &str) -> Self {
+        ApiError::new(ErrorCode::ProfileNotFound, tf("error.profile_not_found", &[("name", &name)]))
+    }
+
+    /// Не удалось прочитать или записать файл приложения.
+    pub fn storage(source: impl fmt::Display) -> Self {
+        ApiError::new(ErrorCode::Storage, t("error.storage").to_string())
+            .caused_by(source)
+    }
+
+    /// Фоновая задача завершилась аварийно.
+    pub fn task_failed(source: impl fmt::Display) -> Self {
+        ApiError::new(ErrorCode::TaskFailed, t("error.task_failed").to_string())
+            .caused_by(source)
+    }
+
+    /// Окно закрылось, пока команда отправляла в него данные.
+    pub fn channel(source: impl fmt::Display) -> Self {
+        ApiError::new(ErrorCode::Channel, t("error.channel").to_string())
+            .caused_by(source)
+    }
+
+    pub fn cancelled() -> Self {
+        ApiError::new(ErrorCode::Cancelled, t("error.cancelled").to_string())
+    }
+}
+
+/// Статус из `u16` или `StatusCode` (`404 Not Found`).
+fn status_code(status: impl fmt::Display) -> Option<u16> {
+    status.to_string().split_whitespace().next()?.parse().ok()
+}
+
+impl From<ShikicrateError> for ApiError {
+    fn from(err: ShikicrateError) -> Self {
+        match err {
+            ShikicrateError::Validation(msg) => {
+                ApiError::new(ErrorCode::InvalidParameter, t("error.invalid_request").to_string())
+                    .caused_by(msg)
+            }
+            ShikicrateError::Http(e) => {
+                ApiError::new(ErrorCode::Network, t("error.network").to_string())
+                    .caused_by(e)
+            }
+            ShikicrateError::GraphQL { message, .. } => {
+                ApiError::new(ErrorCode::GraphqlError, t("error.graphql").to_string())
+                    .caused_by(message)
+            }
+            ShikicrateError::RateLimit { message, retry_after } => {
+                let text = match retry_after {
+                    Some(secs) => tf("error.rate_limited_retry", &[("secs", &secs)]),
+                    None => t("error.rate_limited").to_string(),
+                };
+                let mut error = ApiError::new(ErrorCode::RateLimited, text).with_status(429).caused_by(message);
+                error.retry_after = retry_after;
+                error
+            }
+            ShikicrateError::Api { status, message } => {
+                let code = status_code(&status);
+                let text = tf("error.http_status", &[("status", &status)]);
+                let error = ApiError::new(ErrorCode::HttpStatus, text).caused_by(message);
+                match code {
+                    Some(code) => error.with_status(code),
+                    None => error,
+                }
+            }
+            ShikicrateError::Serialization(e) => {
+                ApiError::new(ErrorCode::InvalidResponse, t("error.invalid_response").to_string())
+                    .caused_by(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_is_read_from_number_or_status_line() {
+        assert_eq!(status_code(503u16), Some(503));
+        assert_eq!(status_code("404 Not Found"), Some(404));
+        assert_eq!(status_code("Not Found"), None);
+    }
+
+    #[test]
+    fn server_errors_and_too_many_requests_are_retryable() {
+        assert!(ApiError::new(ErrorCode::HttpStatus, String::new()).with_status(502).retryable);
+        assert!(ApiError::new(ErrorCode::HttpStatus, String::new()).with_status(429).retryable);
+        assert!(!ApiError::new(ErrorCode::HttpStatus, String::new()).with_status(404).retryable);
+        assert!(ApiError::new(ErrorCode::Network, String::new()).retryable);
+    }
+
+    #[test]
+    fn error_serializes_with_stable_code_and_kind() {
+        let error = ApiError::anime_not_found(5).caused_by("graphql: null");
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["code"], "anime_not_found");
+        assert_eq!(json["kind"], "not_found");
+        assert_eq!(json["source"][0], "graphql: null");
+        assert!(error.to_string().starts_with("anime_not_found: "));
+        assert!(error.to_string().ends_with(" <- graphql: null"));
+    }
+
+    #[test]
+    fn every_code_serializes_as_its_string() {
+        for code in [ErrorCode::InvalidParameter, ErrorCode::RateLimited, ErrorCode::ProfileNotFound, ErrorCode::Cancelled] {
+            assert_eq!(serde_json::to_value(code).unwrap(), code.as_str());
+        }
+    }
+}
@@ -0,0 +1,511 @@
+//! Типизированные значения фильтров поиска и разбор синтаксиса Shikimori
+//! (`tv,movie`, `!music,tv`, `summer_2017`, `2014_2016`, `199x`).
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::i18n::{t, tf};
+
+/// Значение, которое может входить в список фильтра.
+pub trait FilterToken: Sized + fmt::Display {
+    fn parse_token(value: &str) -> Result<Self, String>;
+}
+
+/// Перечисление с фиксированным набором значений и подписями для интерфейса.
+/// Подпись ищется в каталоге строк по ключу `filter_label.<префикс>.<значение>`.
+pub trait FilterEnum: Copy + 'static {
+    const ALL: &'static [Self];
+    fn as_str(self) -> &'static str;
+    fn label(self) -> &'static str;
+}
+
+macro_rules! filter_enum {
+    ($name:ident, $prefix:literal { $($variant:ident => $value:literal;)+ }) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum $name {
+            $($variant,)+
+        }
+
+        impl FilterEnum for $name {
+            const ALL: &'static [Self] = &[$(Self::$variant),+];
+
+            fn as_str(self) -> &'static str {
+                match self { $(Self::$variant => $value,)+ }
+            }
+
+            fn label(self) -> &'static str {
+                match self { $(Self::$variant => t(concat!("filter_label.", $prefix, ".", $value)),)+ }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl FilterToken for $name {
+            fn parse_token(value: &str) -> Result<Self, String> {
+                Self::ALL
+                    .iter()
+                    .copied()
+                    .find(|v| v.as_str() == value)
+                    .ok_or_else(|| {
+                        let allowed = Self::ALL.iter().map(|v| v.as_str()).collect::<Vec<_>>().join(", ");
+                        tf("filter.invalid_value", &[("value", &value), ("allowed", &allowed)])
+                    })
+            }
+        }
+    };
+}
+
+filter_enum!(AnimeKind, "anime_kind" {
+    Tv => "tv";
+    Movie => "movie";
+    Ova => "ova";
+    Ona => "ona";
+    Special => "special";
+    TvSpecial => "tv_special";
+    Music => "music";
+    Pv => "pv";
+    Cm => "cm";
+    Tv13 => "tv_13";
+    Tv24 => "tv_24";
+    Tv48 => "tv_48";
+});
+
+filter_enum!(MangaKind, "manga_kind" {
+    Manga => "manga";
+    Manhwa => "manhwa";
+    Manhua => "manhua";
+    LightNovel => "light_novel";
+    Novel => "novel";
+    OneShot => "one_shot";
+    Doujin => "doujin";
+});
+
+filter_enum!(AnimeStatus, "anime_status" {
+    Anons => "anons";
+    Ongoing => "ongoing";
+    Released => "released";
+});
+
+filter_enum!(MangaStatus, "manga_status" {
+    Anons => "anons";
+    Ongoing => "ongoing";
+    Released => "released";
+    Paused => "paused";
+    Discontinued => "discontinued";
+});
+
+filter_enum!(Rating, "rating" {
+    Unrated => "none";
+    G => "g";
+    Pg => "pg";
+    Pg13 => "pg_13";
+    R => "r";
+    RPlus => "r_plus";
+    Rx => "rx";
+});
+
+filter_enum!(Order, "order" {
+    Id => "id";
+    IdDesc => "id_desc";
+    Ranked => "ranked";
+    Kind => "kind";
+    Popularity => "popularity";
+    Name => "name";
+    AiredOn => "aired_on";
+    Episodes => "episodes";
+    Status => "status";
+    Random => "random";
+    RankedRandom => "ranked_random";
+    RankedShiki => "ranked_shiki";
+    CreatedAt => "created_at";
+    CreatedAtDesc => "created_at_desc";
+});
+
+filter_enum!(SeasonName, "season" {
+    Winter => "winter";
+    Spring => "spring";
+    Summer => "summer";
+    Fall => "fall";
+});
+
+filter_enum!(CharacterRoleKind, "character_role" {
+    Main => "main";
+    Supporting => "supporting";
+});
+
+/// Значение параметра `season`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Season {
+    /// `summer_2017`
+    Quarter(SeasonName, u16),
+    /// `2016`
+    Year(u16),
+    /// `2014_2016`
+    Years(u16, u16),
+    /// `199x`
+    Decade(u16),
+}
+
+const MIN_YEAR: u16 = 1900;
+const MAX_YEAR: u16 = 2100;
+
+fn parse_year(value: &str) -> Option<u16> {
+    if value.len() != 4 || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    value.parse().ok().filter(|y| (MIN_YEAR..=MAX_YEAR).contains(y))
+}
+
+impl FilterToken for Season {
+    fn parse_token(value: &str) -> Result<Self, String> {
+        let invalid = || tf("filter.invalid_season", &[("value", &value)]);
+
+        if let Some(decade) = value.strip_suffix('x') {
+            return match decade.parse::<u16>() {
+                Ok(d) if decade.len() == 3 && (MIN_YEAR / 10..=MAX_YEAR / 10).contains(&d) => Ok(Season::Decade(d)),
+                _ => Err(invalid()),
+            };
+        }
+
+        match value.split_once('_') {
+            None => parse_year(value).map(Season::Year).ok_or_else(invalid),
+            Some((from, to)) => {
+                if let (Some(from), Some(to)) = (parse_year(from), parse_year(to)) {
+                    return if from <= to {
+                        Ok(Season::Years(from, to))
+                    } else {
+                        Err(tf("filter.season_range_reversed", &[("value", &value)]))
+                    };
+                }
+                let name = SeasonName::parse_token(from).map_err(|_| invalid())?;
+                let year = parse_year(to).ok_or_else(invalid)?;
+                Ok(Season::Quarter(name, year))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Season {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Season::Quarter(name, year) => write!(f, "{}_{}", name, year),
+            Season::Year(year) => write!(f, "{}", year),
+            Season::Years(from, to) => write!(f, "{}_{}", from, to),
+            Season::Decade(decade) => write!(f, "{}x", decade),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterItem<T> {
+    pub value: T,
+    pub negated: bool,
+}
+
+/// Список значений через запятую, `!` перед значением исключает его: `!music,tv`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterList<T>(pub Vec<FilterItem<T>>);
+
+impl<T: FilterToken> FromStr for FilterList<T> {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut items = Vec::new();
+        for part in value.split(',') {
+            let part = part.trim();
+            if part.is_empty() {
+                continue;
+            }
+            let (negated, token) = match part.strip_prefix('!') {
+                Some(rest) => (true, rest.trim()),
+                None => (false, part),
+            };
+            items.push(FilterItem { value: T::parse_token(token)?, negated });
+        }
+        Ok(FilterList(items))
+    }
+}
+
+impl<T: PartialEq> FilterList<T> {
+    /// Подходит ли объект с набором значений `values`: есть хотя бы одно из
+    /// перечисленных и нет ни одного исключённого. Пустому фильтру подходит всё.
+    pub fn matches(&self, values: &[T]) -> bool {
+        let mut included = self.0.iter().filter(|item| !item.negated).peekable();
+        let wanted = included.peek().is_none() || included.any(|item| values.contains(&item.value));
+        let excluded = self.0.iter().any(|item| item.negated && values.contains(&item.value));
+        wanted && !excluded
+    }
+}
+
+impl<T: FilterToken> fmt::Display for FilterList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, item) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            if item.negated {
+                f.write_str("!")?;
+            }
+            write!(f, "{}", item.value)?;
+        }
+        Ok(())
+    }
+}
+
+fn parameter_error(param: &str, reason: impl fmt::Display) -> String {
+    tf("validation.parameter", &[("param", &param), ("reason", &reason)])
+}
+
+/// Проверяет строковый фильтр и возвращает его в каноничном виде.
+/// Пустая строка означает отсутствие фильтра.
+pub fn validate_list<T: FilterToken>(param: &str, value: Option<String>) -> Result<Option<String>, String> {
+    let Some(value) = value else { return Ok(None) };
+    let list: FilterList<T> = value.parse().map_err(|e| parameter_error(param, e))?;
+    if list.0.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(list.to_string()))
+}
+
+/// Проверяет сортировку. Значения фронтенда `relevance`, `score` и `title`
+/// понимаются как «без сортировки», `ranked` и `name`.
+pub fn validate_order(value: Option<String>) -> Result<Option<String>, String> {
+    let Some(value) = value else { return Ok(None) };
+    let order = match value.trim() {
+        "" | "relevance" => return Ok(None),
+        "score" => Order::Ranked,
+        "title" => Order::Name,
+        other => Order::parse_token(other).map_err(|e| parameter_error("order", e))?,
+    };
+    Ok(Some(order.to_string()))
+}
+
+#[derive(Debug, Serialize)]
+pub struct FilterOption {
+    value: &'static str,
+    label: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FilterOptions {
+    anime_kinds: Vec<FilterOption>,
+    manga_kinds: Vec<FilterOption>,
+    anime_statuses: Vec<FilterOption>,
+    manga_statuses: Vec<FilterOption>,
+    ratings: Vec<FilterOption>,
+    orders: Vec<FilterOption>,
+    seasons: Vec<FilterOption>,
+    character_roles: Vec<FilterOption>,
+}
+
+fn options_of<T: FilterEnum>() -> Vec<FilterOption> {
+    T::ALL
+        .iter()
+        .map(|v| FilterOption { value: v.as_str(), label: v.label() })
+        .collect()
+}
+
+pub fn filter_options() -> FilterOptions {
+    FilterOptions {
+        anime_kinds: options_of::<AnimeKind>(),
+        manga_kinds: options_of::<MangaKind>(),
+        anime_statuses: options_of::<AnimeStatus>(),
+        manga_statuses: options_of::<MangaStatus>(),
+        ratings: options_of::<Rating>(),
+        orders: options_of::<Order>(),
+        seasons: options_of::<SeasonName>(),
+        character_roles: options_of::<CharacterRoleKind>(),
+    }
+}
+
+/// Числовой диапазон `[min, max]`, любая граница может отсутствовать.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NumericRange<T> {
+    pub min: Option<T>,
+    pub max: Option<T>,
+}
+
+impl<T: PartialOrd + Copy + fmt::Display> NumericRange<T> {
+    pub fn new(min: Option<T>, max: Option<T>) -> Self {
+        Self { min, max }
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.min.is_some() || self.max.is_some()
+    }
+
+    /// Неизвестное значение не проходит заданный диапазон.
+    pub fn contains(&self, value: Option<T>) -> bool {
+        if !self.is_set() {
+            return true;
+        }
+        let Some(value) = value else { return false };
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+
+    pub fn validate(&self, param: &str) -> Result<(), String> {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if min > max => {
+                Err(parameter_error(param, tf("filter.min_greater_than_max", &[("min", &min), ("max", &max)])))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl NumericRange<u16> {
+    /// Диапазон лет как значение `season` для API (`2015_2020`).
+    pub fn as_season(&self) -> Option<Season> {
+        match (self.min, self.max) {
+            (None, None) => None,
+            (Some(from), Some(to)) if from == to => Some(Season::Year(from)),
+            (from, to) => Some(Season::Years(from.unwrap_or(MIN_YEAR), to.unwrap_or(MAX_YEAR))),
+        }
+    }
+
+    pub fn validate_years(&self, param: &str) -> Result<(), String> {
+        for year in [self.min, self.max].into_iter().flatten() {
+            if !(MIN_YEAR..=MAX_YEAR).contains(&year) {
+                let reason = tf("filter.year_out_of_range", &[("year", &year), ("min", &MIN_YEAR), ("max", &MAX_YEAR)]);
+                return Err(parameter_error(param, reason));
+            }
+        }
+        self.validate(param)
+    }
+}
+
+/// Идентификатор жанра в параметре `genre`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenreId(pub i64);
+
+impl FilterToken for GenreId {
+    fn parse_token(value: &str) -> Result<Self, String> {
+        value
+            .parse::<i64>()
+            .ok()
+            .filter(|id| *id > 0)
+            .map(GenreId)
+            .ok_or_else(|| tf("filter.invalid_genre_id", &[("value", &value)]))
+    }
+}
+
+impl fmt::Display for GenreId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Собирает параметр `genre` из строки фронтенда и списков включаемых
+/// и исключаемых жанров: `1,2,!12`.
+pub fn build_genre_filter(genre: Option<String>, include: &[i64], exclude: &[i64]) -> Result<Option<String>, String> {
+    let mut list: FilterList<GenreId> = genre
+        .as_deref()
+        .unwrap_or_default()
+        .parse()
+        .map_err(|e| parameter_error("genre", e))?;
+
+    let items = include
+        .iter()
+        .map(|id| (*id, false))
+        .chain(exclude.iter().map(|id| (*id, true)));
+    for (id, negated) in items {
+        let value = GenreId::parse_token(&id.to_string()).map_err(|e| parameter_error("genre", e))?;
+        list.0.push(FilterItem { value, negated });
+    }
+
+    let mut seen: Vec<FilterItem<GenreId>> = Vec::new();
+    for item in list.0 {
+        match seen.iter().find(|s| s.value == item.value) {
+            Some(s) if s.negated != item.negated => {
+                return Err(tf("filter.genre_conflict", &[("id", &item.value)]));
+            }
+            Some(_) => {}
+            None => seen.push(item),
+        }
+    }
+
+    if seen.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(FilterList(seen).to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_kind_is_rejected() {
+        let error = validate_list::<AnimeKind>("kind", Some("tv,cartoon".to_string())).unwrap_err();
+        assert!(error.contains("kind") && error.contains("\"cartoon\""), "{}", error);
+        assert!(validate_list::<MangaKind>("kind", Some("tv".to_string())).is_err());
+    }
+
+    #[test]
+    fn kind_list_is_canonicalized() {
+        assert_eq!(validate_list::<AnimeKind>("kind", Some(" !music , tv ".to_string())), Ok(Some("!music,tv".to_string())));
+        assert_eq!(validate_list::<AnimeKind>("kind", Some(" , ".to_string())), Ok(None));
+        assert_eq!(validate_list::<AnimeKind>("kind", None), Ok(None));
+    }
+
+    #[test]
+    fn unknown_status_is_rejected() {
+        assert!(validate_list::<AnimeStatus>("status", Some("finished".to_string())).is_err());
+        assert!(validate_list::<AnimeStatus>("status", Some("Ongoing".to_string())).is_err());
+        // Статус есть только у манги
+        assert!(validate_list::<AnimeStatus>("status", Some("paused".to_string())).is_err());
+        assert_eq!(validate_list::<MangaStatus>("status", Some("paused".to_string())), Ok(Some("paused".to_string())));
+    }
+
+    #[test]
+    fn unknown_order_is_rejected() {
+        let error = validate_order(Some("rating".to_string())).unwrap_err();
+        assert!(error.contains("order") && error.contains("\"rating\""), "{}", error);
+        assert!(validate_order(Some("Popularity".to_string())).is_err());
+    }
+
+    #[test]
+    fn frontend_orders_are_mapped() {
+        assert_eq!(validate_order(Some("popularity".to_string())), Ok(Some("popularity".to_string())));
+        assert_eq!(validate_order(Some("score".to_string())), Ok(Some("ranked".to_string())));
+        assert_eq!(validate_order(Some("title".to_string())), Ok(Some("name".to_string())));
+        assert_eq!(validate_order(Some("relevance".to_string())), Ok(None));
+        assert_eq!(validate_order(None), Ok(None));
+    }
+
+    #[test]
+    fn invalid_seasons_are_rejected() {
+        assert!(validate_list::<Season>("season", Some("autumn_2017".to_string())).is_err());
+        assert!(validate_list::<Season>("season", Some("2016_2014".to_string())).is_err());
+        assert_eq!(
+            validate_list::<Season>("season", Some("summer_2017,199x".to_string())),
+            Ok(Some("summer_2017,199x".to_string()))
+        );
+    }
+
+    #[test]
+    fn genre_lists_are_merged_without_duplicates() {
+        assert_eq!(build_genre_filter(Some("1,!12".to_string()), &[2, 1], &[40]), Ok(Some("1,!12,2,!40".to_string())));
+        assert_eq!(build_genre_filter(None, &[], &[]), Ok(None));
+    }
+
+    #[test]
+    fn genre_both_included_and_excluded_is_rejected() {
+        assert!(build_genre_filter(Some("12".to_string()), &[], &[12]).is_err());
+        assert!(build_genre_filter(None, &[0], &[]).is_err());
+    }
+
+    #[test]
+    fn genre_list_matches_included_and_excluded() {
+        let list: FilterList<GenreId> = "1,2,!12".parse().unwrap();
+        assert!(list.matches(&[GenreId(2)]));
+        assert!(!list.matches(&[GenreId(2), GenreId(12)]));
+        assert!(!list.matches(&[GenreId(3)]));
+    }
+}
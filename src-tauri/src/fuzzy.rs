@@ -0,0 +1,205 @@
+//! Нечёткое сравнение названий для переранжирования выдачи поиска: регистр,
+//! диакритика, варианты ромадзи (`Tōkyō`, `Toukyou`, `Tokyo`), кириллица
+//! и опечатки. Плюс исправление запроса, набранного не в той раскладке.
+
+/// Клавиши QWERTY и ЙЦУКЕН в одном и том же порядке.
+const QWERTY: &str = "qwertyuiop[]asdfghjkl;'zxcvbnm,.`";
+const JCUKEN: &str = "йцукенгшщзхъфывапролджэячсмитьбюё";
+
+fn strip_diacritic(c: char) -> char {
+    match c {
+        'ā' | 'â' | 'á' | 'à' | 'ä' => 'a',
+        'ē' | 'ê' | 'é' | 'è' | 'ë' => 'e',
+        'ī' | 'î' | 'í' | 'ì' | 'ï' => 'i',
+        'ō' | 'ô' | 'ó' | 'ò' | 'ö' => 'o',
+        'ū' | 'û' | 'ú' | 'ù' | 'ü' => 'u',
+        'ё' => 'е',
+        c => c,
+    }
+}
+
+fn transliterate(c: char) -> Option<&'static str> {
+    Some(match c {
+        'а' => "a", 'б' => "b", 'в' => "v", 'г' => "g", 'д' => "d", 'е' => "e",
+        'ж' => "zh", 'з' => "z", 'и' => "i", 'й' => "y", 'к' => "k", 'л' => "l",
+        'м' => "m", 'н' => "n", 'о' => "o", 'п' => "p", 'р' => "r", 'с' => "s",
+        'т' => "t", 'у' => "u", 'ф' => "f", 'х' => "kh", 'ц' => "ts", 'ч' => "ch",
+        'ш' => "sh", 'щ' => "shch", 'ъ' | 'ь' => "", 'ы' => "y", 'э' => "e",
+        'ю' => "yu", 'я' => "ya",
+        _ => return None,
+    })
+}
+
+/// Приводит название к виду для сравнения: нижний регистр без диакритики
+/// и знаков препинания, кириллица в латинице, долгие гласные ромадзи схлопнуты.
+pub fn normalize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase).map(strip_diacritic) {
+        if let Some(latin) = transliterate(c) {
+            out.push_str(latin);
+        } else if c.is_alphanumeric() {
+            out.push(c);
+        } else if !out.is_empty() && !out.ends_with(' ') {
+            out.push(' ');
+        }
+    }
+    out.trim_end().replace("ou", "o").replace("oo", "o").replace("uu", "u")
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(prev[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut current);
+    }
+    prev[b.len()]
+}
+
+fn edit_similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 0.0;
+    }
+    1.0 - levenshtein(&a, &b) as f64 / longest as f64
+}
+
+fn token_similarity(query: &str, token: &str) -> f64 {
+    if query == token {
+        1.0
+    } else if query.chars().count() >= 2 && token.starts_with(query) {
+        0.9
+    } else {
+        edit_similarity(query, token)
+    }
+}
+
+/// Похожесть уже нормализованных запроса и названия, от 0 до 1.
+fn similarity(query: &str, name: &str) -> f64 {
+    if query.is_empty() || name.is_empty() {
+        return 0.0;
+    }
+    if query == name {
+        return 1.0;
+    }
+    if name.starts_with(query) {
+        return 0.95;
+    }
+    if name.contains(query) {
+        return 0.9;
+    }
+
+    let tokens: Vec<&str> = name.split(' ').collect();
+    let query_tokens: Vec<&str> = query.split(' ').collect();
+    let by_tokens = query_tokens
+        .iter()
+        .map(|q| tokens.iter().map(|t| token_similarity(q, t)).fold(0.0, f64::max))
+        .sum::<f64>()
+        / query_tokens.len() as f64;
+
+    0.85 * by_tokens.max(edit_similarity(query, name))
+}
+
+/// Лучшая похожесть запроса на любое из названий.
+pub fn score<'a>(query: &str, names: impl IntoIterator<Item = &'a str>) -> f64 {
+    let query = normalize(query);
+    names
+        .into_iter()
+        .map(|name| similarity(&query, &normalize(name)))
+        .fold(0.0, f64::max)
+}
+
+/// Переупорядочивает выдачу по похожести названий на запрос. Позиция в выдаче
+/// API немного учитывается, чтобы при равной похожести сохранялся её порядок.
+pub fn rerank<T>(items: Vec<(f64, T)>) -> Vec<T> {
+    let total = items.len().max(1) as f64;
+    let mut ranked: Vec<(f64, T)> = items
+        .into_iter()
+        .enumerate()
+        .map(|(position, (score, item))| (score + 0.1 * (1.0 - position as f64 / total), item))
+        .collect();
+    ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+    ranked.into_iter().map(|(_, item)| item).collect()
+}
+
+/// Похожесть, ниже которой название не считается совпадением с запросом:
+/// пропускает опечатку-другую в слове, но не соседние по написанию тайтлы.
+pub const MIN_MATCH_SCORE: f64 = 0.6;
+
+/// Для списков, которые ищутся целиком на бэкенде: отбрасывает несовпавшие
+/// элементы и упорядочивает остальные так же, как [`rerank`].
+pub fn rank_matches<T>(items: Vec<(f64, T)>) -> Vec<T> {
+    rerank(items.into_iter().filter(|(score, _)| *score >= MIN_MATCH_SCORE).collect())
+}
+
+/// Переводит запрос, набранный не в той раскладке: `yfhenj` ↔ `наруто`.
+/// Возвращает `None`, если в запросе смешаны алфавиты или нечего менять.
+pub fn switch_layout(query: &str) -> Option<String> {
+    let query = query.to_lowercase();
+    let has_latin = query.chars().any(|c| c.is_ascii_alphabetic());
+    let has_cyrillic = query.chars().any(|c| JCUKEN.contains(c));
+    let (from, to) = match (has_latin, has_cyrillic) {
+        (true, false) => (QWERTY, JCUKEN),
+        (false, true) => (JCUKEN, QWERTY),
+        _ => return None,
+    };
+
+    let switched: String = query
+        .chars()
+        .map(|c| from.chars().position(|k| k == c).and_then(|i| to.chars().nth(i)).unwrap_or(c))
+        .collect();
+    (switched != query).then_some(switched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_query_scores_zero() {
+        assert_eq!(normalize(""), "");
+        assert_eq!(score("", ["Naruto", "Наруто"]), 0.0);
+        assert_eq!(score("  ,. ", ["Naruto"]), 0.0);
+        assert_eq!(score("naruto", [""]), 0.0);
+    }
+
+    #[test]
+    fn empty_query_keeps_api_order() {
+        assert_eq!(rerank(vec![(0.0, "a"), (0.0, "b"), (0.0, "c")]), vec!["a", "b", "c"]);
+        assert!(rerank(Vec::<(f64, &str)>::new()).is_empty());
+    }
+
+    #[test]
+    fn rerank_puts_best_match_first() {
+        let names = ["Boruto", "Naruto: Shippuuden", "Naruto"];
+        let items = names.iter().map(|n| (score("наруто", [*n]), *n)).collect();
+        assert_eq!(rerank(items), vec!["Naruto", "Naruto: Shippuuden", "Boruto"]);
+    }
+
+    #[test]
+    fn empty_query_has_no_layout_switch() {
+        assert_eq!(switch_layout(""), None);
+        assert_eq!(switch_layout("   "), None);
+        assert_eq!(switch_layout("2023"), None);
+    }
+
+    #[test]
+    fn layout_is_switched_both_ways() {
+        assert_eq!(switch_layout("yfhenj").as_deref(), Some("наруто"));
+        assert_eq!(switch_layout("Наруто").as_deref(), Some("yfhenj"));
+        assert_eq!(switch_layout("naruто"), None);
+    }
+
+    #[test]
+    fn rank_matches_tolerates_typos_and_drops_unrelated() {
+        let names = ["Boruto Uzumaki", "Sasuke Uchiha", "Naruto Uzumaki"];
+        let items = names.iter().map(|n| (score("narto", [*n]), *n)).collect();
+        assert_eq!(rank_matches(items), vec!["Naruto Uzumaki"]);
+    }
+}
@@ -0,0 +1,238 @@
+//! История поиска: запрос вместе с разделом, фильтрами и открытым результатом.
+//! Записи ранжируются по частоте и давности использования (frecency).
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+pub const HISTORY_FILE: &str = "search_history.json";
+const MAX_ENTRIES: usize = 200;
+const DAY: u64 = 24 * 60 * 60;
+
+// Запись идёт через чтение-изменение-запись файла, параллельные команды не должны
+// затирать изменения друг друга
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentType {
+    Anime,
+    Manga,
+    Characters,
+    People,
+}
+
+/// Результат, который пользователь открыл из выдачи по этому запросу.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClickedResult {
+    pub id: i64,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: u64,
+    pub query: String,
+    pub content_type: ContentType,
+    #[serde(default)]
+    pub filters: BTreeMap<String, String>,
+    pub clicked: Option<ClickedResult>,
+    pub count: u32,
+    pub first_used: u64,
+    pub last_used: u64,
+}
+
+impl HistoryEntry {
+    fn matches(&self, query: &str, content_type: ContentType, filters: &BTreeMap<String, String>) -> bool {
+        self.content_type == content_type && self.query.to_lowercase() == query.to_lowercase() && &self.filters == filters
+    }
+
+    /// Число использований с весом, который падает с давностью последнего из них.
+    pub fn frecency(&self, now: u64) -> f64 {
+        let weight = match now.saturating_sub(self.last_used) / DAY {
+            0..=3 => 100.0,
+            4..=14 => 70.0,
+            15..=31 => 50.0,
+            32..=90 => 30.0,
+            _ => 10.0,
+        };
+        self.count as f64 * weight
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct HistoryFile {
+    next_id: u64,
+    entries: Vec<HistoryEntry>,
+}
+
+/// Подсказка для строки поиска: прошлый запрос или тайтл из автодополнения.
+#[derive(Debug, Serialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum SearchSuggestion {
+    History { id: u64, query: String, content_type: ContentType },
+    Anime { id: i64, title: String, russian: Option<String> },
+}
+
+fn sorted(entries: &mut [HistoryEntry]) {
+    let now = storage::now();
+    entries.sort_by(|a, b| b.frecency(now).total_cmp(&a.frecency(now)).then(b.last_used.cmp(&a.last_used)));
+}
+
+/// Записывает запрос; повторный запрос с теми же разделом и фильтрами
+/// увеличивает счётчик существующей записи.
+pub fn record(
+    path: PathBuf,
+    query: &str,
+    content_type: ContentType,
+    filters: BTreeMap<String, String>,
+    clicked: Option<ClickedResult>,
+) -> Result<(), String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(());
+    }
+
+    let _guard = FILE_LOCK.lock().unwrap();
+    let mut history: HistoryFile = storage::load(&path);
+    let now = storage::now();
+
+    match history.entries.iter_mut().find(|e| e.matches(query, content_type, &filters)) {
+        Some(entry) => {
+            entry.query = query.to_string();
+            // Клик по результату не считается отдельным поиском
+            if clicked.is_some() {
+                entry.clicked = clicked;
+            } else {
+                entry.count += 1;
+            }
+            entry.last_used = now;
+        }
+        None => {
+            history.next_id += 1;
+            history.entries.push(HistoryEntry {
+                id: history.next_id,
+                query: query.to_string(),
+                content_type,
+                filters,
+                clicked,
+                count: 1,
+                first_used: now,
+                last_used: now,
+            });
+        }
+    }
+
+    if history.entries.len() > MAX_ENTRIES {
+        sorted(&mut history.entries);
+        history.entries.truncate(MAX_ENTRIES);
+    }
+    storage::save(&path, &history)
+}
+
+/// Записи по убыванию frecency, при `content_type` только этого раздела.
+pub fn list(path: PathBuf, content_type: Option<ContentType>, limit: usize) -> Vec<HistoryEntry> {
+    let _guard = FILE_LOCK.lock().unwrap();
+    let mut entries: Vec<HistoryEntry> = storage::load::<HistoryFile>(&path)
+        .entries
+        .into_iter()
+        .filter(|e| content_type.is_none_or(|t| e.content_type == t))
+        .collect();
+    sorted(&mut entries);
+    entries.truncate(limit);
+    entries
+}
+
+/// Прошлые запросы, начинающиеся с `prefix` (или одно из слов которых начинается с него).
+/// Одинаковые запросы с разными фильтрами схлопываются в один.
+pub fn suggestions(path: PathBuf, prefix: &str, content_type: Option<ContentType>, limit: usize) -> Vec<HistoryEntry> {
+    let prefix = prefix.trim().to_lowercase();
+    let mut seen = Vec::new();
+    list(path, content_type, usize::MAX)
+        .into_iter()
+        .filter(|e| {
+            let query = e.query.to_lowercase();
+            query.starts_with(&prefix) || query.split_whitespace().any(|word| word.starts_with(&prefix))
+        })
+        .filter(|e| {
+            let key = e.query.to_lowercase();
+            let fresh = !seen.contains(&key);
+            seen.push(key);
+            fresh
+        })
+        .take(limit)
+        .collect()
+}
+
+pub fn remove(path: PathBuf, id: u64) -> Result<(), String> {
+    let _guard = FILE_LOCK.lock().unwrap();
+    let mut history: HistoryFile = storage::load(&path);
+    history.entries.retain(|e| e.id != id);
+    storage::save(&path, &history)
+}
+
+pub fn clear(path: PathBuf) -> Result<(), String> {
+    let _guard = FILE_LOCK.lock().unwrap();
+    let mut history: HistoryFile = storage::load(&path);
+    history.entries.clear();
+    storage::save(&path, &history)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: u64, query: &str, count: u32, days_ago: u64) -> HistoryEntry {
+        let last_used = storage::now() - days_ago * DAY;
+        HistoryEntry {
+            id,
+            query: query.to_string(),
+            content_type: ContentType::Anime,
+            filters: BTreeMap::new(),
+            clicked: None,
+            count,
+            first_used: last_used,
+            last_used,
+        }
+    }
+
+    fn temp_history(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("shikimore-{}-{}.json", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn recent_use_outweighs_old_frequent_use() {
+        let now = storage::now();
+        assert!(entry(1, "a", 2, 1).frecency(now) > entry(2, "b", 5, 100).frecency(now));
+        assert!(entry(1, "a", 2, 1).frecency(now) > entry(2, "b", 1, 1).frecency(now));
+    }
+
+    #[test]
+    fn entries_are_sorted_by_frecency_then_recency() {
+        let mut entries = vec![entry(1, "old", 5, 100), entry(2, "fresh", 1, 0), entry(3, "often", 3, 2), entry(4, "older fresh", 1, 1)];
+        sorted(&mut entries);
+        assert_eq!(entries.iter().map(|e| e.id).collect::<Vec<_>>(), vec![3, 2, 4, 1]);
+    }
+
+    #[test]
+    fn repeated_query_increments_one_entry() {
+        let path = temp_history("history-repeat");
+        record(path.clone(), "Naruto", ContentType::Anime, BTreeMap::new(), None).unwrap();
+        record(path.clone(), " naruto ", ContentType::Anime, BTreeMap::new(), None).unwrap();
+        record(path.clone(), "naruto", ContentType::Manga, BTreeMap::new(), None).unwrap();
+        record(path.clone(), "naruto", ContentType::Anime, BTreeMap::new(), Some(ClickedResult { id: 20, title: "Naruto".to_string() })).unwrap();
+
+        let entries = list(path.clone(), Some(ContentType::Anime), 10);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].count, 2);
+        assert_eq!(entries[0].clicked.as_ref().map(|c| c.id), Some(20));
+        assert_eq!(suggestions(path.clone(), "NAR", None, 10).len(), 1);
+        let _ = std::fs::remove_file(path);
+    }
+}
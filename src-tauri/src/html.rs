@@ -0,0 +1,491 @@
+//! Очистка `description_html` от Shikimori: белый список тегов и атрибутов,
+//! ссылки на сущности переписываются в навигацию внутри приложения (`shikimore://anime/5`).
+
+use crate::bbcode::EntityKind;
+use crate::i18n::t;
+use crate::spoilers::SpoilerMode;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SanitizeOptions {
+    /// Сворачивать блочные спойлеры в `<details>` вместо разметки Shikimori.
+    pub collapse_spoilers: bool,
+    pub spoiler_mode: SpoilerMode,
+}
+
+const ALLOWED_TAGS: &[&str] = &[
+    "a", "b", "strong", "i", "em", "u", "s", "del", "strike", "sub", "sup", "br", "hr",
+    "p", "div", "span", "ul", "ol", "li", "blockquote", "center", "h2", "h3", "h4",
+    "code", "pre", "img",
+];
+
+// Вырезаются вместе с содержимым.
+const DROPPED_TAGS: &[&str] = &[
+    "script", "style", "iframe", "object", "embed", "noscript", "template", "svg",
+    "form", "button", "input", "textarea", "select", "head", "title",
+];
+
+// Корневые элементы спойлеров; вложенные b-spoiler_label и т. п. спойлерами не считаются.
+const SPOILER_CLASSES: &[&str] = &["b-spoiler", "b-spoiler_block", "b-spoiler_inline"];
+
+const VOID_TAGS: &[&str] = &["br", "hr", "img", "input", "meta", "link", "wbr", "source"];
+
+const SHIKIMORI_HOSTS: &[&str] = &["shikimori.one", "shikimori.me", "shikimori.org", "shikimori.io"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'a> {
+    Text(&'a str),
+    Start { name: String, attrs: Vec<(String, String)> },
+    End { name: String },
+}
+
+impl Token<'_> {
+    fn attr(&self, key: &str) -> Option<&str> {
+        match self {
+            Token::Start { attrs, .. } => attrs.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str()),
+            _ => None,
+        }
+    }
+
+    fn has_class(&self, class: &str) -> bool {
+        self.attr("class")
+            .is_some_and(|c| c.split_whitespace().any(|c| c == class))
+    }
+}
+
+fn tokenize(input: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < input.len() {
+        let rest = &input[pos..];
+        let Some(lt) = rest.find('<') else {
+            tokens.push(Token::Text(rest));
+            break;
+        };
+        if lt > 0 {
+            tokens.push(Token::Text(&rest[..lt]));
+            pos += lt;
+            continue;
+        }
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            pos += 4 + comment.find("-->").map(|e| e + 3).unwrap_or(comment.len());
+            continue;
+        }
+
+        match parse_tag(rest) {
+            Some((token, len)) => {
+                if let Some(token) = token {
+                    tokens.push(token);
+                }
+                pos += len;
+            }
+            None => {
+                tokens.push(Token::Text("&lt;"));
+                pos += 1;
+            }
+        }
+    }
+
+    tokens
+}
+
+/// Разбирает тег в начале строки. `Some((None, len))` — служебная конструкция вроде `<!DOCTYPE>`.
+fn parse_tag(input: &str) -> Option<(Option<Token<'_>>, usize)> {
+    let bytes = input.as_bytes();
+    let closing = bytes.get(1) == Some(&b'/');
+    let name_start = if closing { 2 } else { 1 };
+
+    if bytes.get(1) == Some(&b'!') || bytes.get(1) == Some(&b'?') {
+        return Some((None, input.find('>').map(|e| e + 1).unwrap_or(input.len())));
+    }
+    if !bytes.get(name_start).is_some_and(u8::is_ascii_alphabetic) {
+        return None;
+    }
+
+    let name_end = input[name_start..]
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .map(|e| e + name_start)?;
+    let name = input[name_start..name_end].to_ascii_lowercase();
+
+    let mut attrs = Vec::new();
+    let mut pos = name_end;
+    loop {
+        let rest = &input[pos..];
+        let trimmed = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        pos += rest.len() - trimmed.len();
+        if trimmed.is_empty() {
+            return None;
+        }
+        if trimmed.starts_with('>') {
+            pos += 1;
+            break;
+        }
+
+        let key_len = trimmed
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '>' || c == '/')
+            .unwrap_or(trimmed.len());
+        let key = trimmed[..key_len].to_ascii_lowercase();
+        pos += key_len;
+
+        let rest = &input[pos..];
+        let after_ws = rest.trim_start();
+        if let Some(value_part) = after_ws.strip_prefix('=') {
+            let value_part = value_part.trim_start();
+            pos += rest.len() - value_part.len();
+            let (value, len) = match value_part.chars().next() {
+                Some(q @ ('"' | '\'')) => {
+                    let end = value_part[1..].find(q)?;
+                    (&value_part[1..end + 1], end + 2)
+                }
+                _ => {
+                    let end = value_part
+                        .find(|c: char| c.is_whitespace() || c == '>')
+                        .unwrap_or(value_part.len());
+                    (&value_part[..end], end)
+                }
+            };
+            attrs.push((key, decode_entities(value)));
+            pos += len;
+        } else if !key.is_empty() {
+            attrs.push((key, String::new()));
+        }
+    }
+
+    let token = if closing { Token::End { name } } else { Token::Start { name, attrs } };
+    Some((Some(token), pos))
+}
+
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+fn escape_attr(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Индекс токена, закрывающего элемент, открытый в `tokens[start]`.
+fn matching_end(tokens: &[Token<'_>], start: usize) -> usize {
+    let Token::Start { name, .. } = &tokens[start] else { return start };
+    let mut depth = 0usize;
+    for (i, token) in tokens.iter().enumerate().skip(start + 1) {
+        match token {
+            Token::Start { name: n, .. } if n == name => depth += 1,
+            Token::End { name: n } if n == name => {
+                if depth == 0 {
+                    return i;
+                }
+                depth -= 1;
+            }
+            _ => {}
+        }
+    }
+    tokens.len()
+}
+
+fn text_of(tokens: &[Token<'_>]) -> String {
+    tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Text(text) => Some(*text),
+            _ => None,
+        })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn absolute_url(url: &str) -> String {
+    if let Some(rest) = url.strip_prefix("//") {
+        format!("https://{}", rest)
+    } else if url.starts_with('/') {
+        format!("https://shikimori.one{}", url)
+    } else {
+        url.to_string()
+    }
+}
+
+/// Переписывает ссылку на страницу Shikimori в `shikimore://<entity>/<id>`.
+pub fn in_app_link(href: &str) -> Option<String> {
+    let path = if href.starts_with('/') && !href.starts_with("//") {
+        href
+    } else {
+        let rest = href
+            .strip_prefix("https://")
+            .or_else(|| href.strip_prefix("http://"))
+            .or_else(|| href.strip_prefix("//"))?;
+        let (host, path) = rest.split_at(rest.find('/')?);
+        let host = host.strip_prefix("www.").unwrap_or(host);
+        if !SHIKIMORI_HOSTS.contains(&host) {
+            return None;
+        }
+        path
+    };
+
+    let mut segments = path.trim_start_matches('/').split(['/', '?', '#']);
+    let entity = match segments.next()? {
+        "animes" => EntityKind::Anime,
+        "mangas" => EntityKind::Manga,
+        "ranobe" => EntityKind::Ranobe,
+        "characters" => EntityKind::Character,
+        "people" | "persons" => EntityKind::Person,
+        _ => return None,
+    };
+    // Идентификаторы бывают с буквенным префиксом: /animes/z40748-name
+    let slug = segments.next()?.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+    let digits_end = slug.find(|c: char| !c.is_ascii_digit()).unwrap_or(slug.len());
+    let id: i64 = slug[..digits_end].parse().ok()?;
+
+    Some(entity.app_url(id))
+}
+
+fn safe_href(href: &str) -> Option<String> {
+    if let Some(link) = in_app_link(href) {
+        return Some(link);
+    }
+    let url = absolute_url(href);
+    if url.starts_with("https://") || url.starts_with("http://") {
+        Some(url)
+    } else {
+        None
+    }
+}
+
+struct Sanitizer {
+    options: SanitizeOptions,
+    out: String,
+}
+
+impl Sanitizer {
+    fn write_start(&mut self, name: &str, attrs: &[(&str, String)]) {
+        self.out.push('<');
+        self.out.push_str(name);
+        for (key, value) in attrs {
+            self.out.push_str(&format!(" {}=\"{}\"", key, escape_attr(value)));
+        }
+        self.out.push('>');
+    }
+
+    fn write_end(&mut self, name: &str) {
+        self.out.push_str(&format!("</{}>", name));
+    }
+
+    fn run(&mut self, tokens: &[Token<'_>]) {
+        // Открытые элементы: исходное имя тега и имя, под которым он выведен (если выведен)
+        let mut stack: Vec<(String, Option<&'static str>)> = Vec::new();
+        let mut i = 0;
+
+        while i < tokens.len() {
+            let token = &tokens[i];
+            match token {
+                Token::Text(text) => self.out.push_str(&text.replace('>', "&gt;")),
+                Token::End { name } => {
+                    if let Some(pos) = stack.iter().rposition(|(n, _)| n == name) {
+                        for (_, emitted) in stack.split_off(pos).into_iter().rev() {
+                            if let Some(tag) = emitted {
+                                self.write_end(tag);
+                            }
+                        }
+                    }
+                }
+                Token::Start { name, .. } => {
+                    if DROPPED_TAGS.contains(&name.as_str()) {
+                        i = matching_end(tokens, i) + 1;
+                        continue;
+                    }
+                    if SPOILER_CLASSES.iter().any(|c| token.has_class(c)) {
+                        let end = matching_end(tokens, i);
+                        self.spoiler(token, &tokens[i + 1..end.min(tokens.len())]);
+                        i = end + 1;
+                        continue;
+                    }
+
+                    let emitted = self.element(token);
+                    if !VOID_TAGS.contains(&name.as_str()) {
+                        stack.push((name.clone(), emitted));
+                    }
+                }
+            }
+            i += 1;
+        }
+
+        for (_, emitted) in stack.into_iter().rev() {
+            if let Some(tag) = emitted {
+                self.write_end(tag);
+            }
+        }
+    }
+
+    /// Выводит разрешённый открывающий тег и возвращает его имя. `None` — тег отброшен
+    /// (его содержимое при этом остаётся).
+    fn element(&mut self, token: &Token<'_>) -> Option<&'static str> {
+        let Token::Start { name, .. } = token else { return None };
+        let tag = *ALLOWED_TAGS.iter().find(|t| **t == name)?;
+
+        match tag {
+            "a" => {
+                let href = token.attr("href").and_then(safe_href)?;
+                let mut attrs = vec![("href", href)];
+                if let Some(title) = token.attr("title") {
+                    attrs.push(("title", title.to_string()));
+                }
+                self.write_start("a", &attrs);
+                Some("a")
+            }
+            "img" => {
+                let src = token.attr("src").map(absolute_url).filter(|s| s.starts_with("https://") || s.starts_with("http://"));
+                if let Some(src) = src {
+                    let alt = token.attr("alt").unwrap_or_default().to_string();
+                    self.write_start("img", &[("src", src), ("alt", alt)]);
+                }
+                None
+            }
+            // Цитаты Shikimori приходят как div.b-quote
+            "div" if token.has_class("b-quote") => {
+                self.write_start("blockquote", &[]);
+                Some("blockquote")
+            }
+            _ => {
+                self.write_start(tag, &[]);
+                Some(tag)
+            }
+        }
+    }
+
+    // Спойлеры бывают трёх видов:
+    //   <div class="b-spoiler"><label>…</label><div class="content"><div class="inner">…</div></div></div>
+    //   <div class="b-spoiler_block"><span>…</span><div>…</div></div>
+    //   <span class="b-spoiler_inline"><span>…</span></span>
+    fn spoiler(&mut self, token: &Token<'_>, inner: &[Token<'_>]) {
+        if self.options.spoiler_mode == SpoilerMode::Strip {
+            return;
+        }
+        let inline = token.has_class("b-spoiler_inline");
+        let label_pos = if inline {
+            None
+        } else {
+            inner.iter().position(|t| {
+                matches!(t, Token::Start { name, .. } if name == "label" || name == "span")
+            })
+        };
+
+        let (label, content) = match label_pos {
+            Some(start) => {
+                let end = matching_end(inner, start).min(inner.len());
+                let content: Vec<Token<'_>> = inner[..start]
+                    .iter()
+                    .chain(inner.get(end + 1..).unwrap_or_default())
+                    .cloned()
+                    .collect();
+                (text_of(&inner[start + 1..end]), content)
+            }
+            // У строчного спойлера содержимое обёрнуто в собственный span
+            None if inline && matches!(inner.first(), Some(Token::Start { name, .. }) if name == "span")
+                && matching_end(inner, 0) + 1 == inner.len() =>
+            {
+                (String::new(), inner[1..inner.len() - 1].to_vec())
+            }
+            None => (String::new(), inner.to_vec()),
+        };
+        let label = if label.is_empty() { t("html.spoiler").to_string() } else { label.replace('>', "&gt;") };
+
+        let mut nested = Sanitizer { options: self.options, out: String::new() };
+        nested.run(&content);
+        let content = nested.out;
+
+        if self.options.spoiler_mode == SpoilerMode::Show {
+            let tag = if inline { "span" } else { "div" };
+            self.out.push_str(&format!("<{tag}>{}</{tag}>", content));
+        } else if inline {
+            self.out.push_str(&format!("<span class=\"b-spoiler_inline\"><span>{}</span></span>", content));
+        } else if self.options.collapse_spoilers {
+            self.out.push_str(&format!("<details class=\"spoiler\"><summary>{}</summary><div>{}</div></details>", label, content));
+        } else {
+            self.out.push_str(&format!(
+                "<div class=\"b-spoiler_block\"><span class=\"b-spoiler_label\">{}</span><div>{}</div></div>",
+                label, content
+            ));
+        }
+    }
+}
+
+/// Очищает HTML описания: оставляет только разрешённые теги без атрибутов
+/// (кроме ссылок и картинок), убирает разметку всплывающих подсказок Shikimori.
+pub fn sanitize(html: &str, options: SanitizeOptions) -> String {
+    let tokens = tokenize(html);
+    let mut sanitizer = Sanitizer { options, out: String::new() };
+    sanitizer.run(&tokens);
+    sanitizer.out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clean(html: &str) -> String {
+        sanitize(html, SanitizeOptions::default())
+    }
+
+    #[test]
+    fn javascript_links_are_dropped() {
+        assert_eq!(clean("<a href=\"javascript:alert(1)\">x</a>"), "x");
+        assert_eq!(clean("<a href=\" JaVaScRiPt:alert(1)\">x</a>"), "x");
+        assert_eq!(clean("<a href=javascript:alert(1)>x</a>"), "x");
+        assert_eq!(clean("<img src=\"javascript:alert(1)\">"), "");
+    }
+
+    #[test]
+    fn http_links_keep_only_allowed_attributes() {
+        assert_eq!(
+            clean("<a href=\"https://example.com\" onclick=\"alert(1)\">y</a>"),
+            "<a href=\"https://example.com\">y</a>"
+        );
+    }
+
+    #[test]
+    fn shikimori_links_become_in_app() {
+        assert_eq!(
+            clean("<a href=\"https://shikimori.one/animes/z40748-name\">N</a>"),
+            "<a href=\"shikimore://anime/40748\">N</a>"
+        );
+        assert_eq!(in_app_link("/characters/5"), Some("shikimore://character/5".to_string()));
+        assert_eq!(in_app_link("javascript:alert(1)"), None);
+    }
+
+    #[test]
+    fn scripts_are_removed_with_content() {
+        assert_eq!(clean("a<script>alert(1)</script>b"), "ab");
+    }
+
+    #[test]
+    fn shikimori_spoiler_markup_is_recognized() {
+        let old = "до<div class=\"b-spoiler unprocessed\"><label>спойлер</label><div class=\"content\"><div class=\"before\"></div><div class=\"inner\">тайна</div><div class=\"after\"></div></div></div>после";
+        let block = "до<div class=\"b-spoiler_block to-process\" data-dynamic=\"spoiler_block\"><span tabindex=\"0\">спойлер</span><div>тайна</div></div>после";
+        let inline = "до<span class=\"b-spoiler_inline to-process\" data-dynamic=\"spoiler_inline\"><span>тайна</span></span>после";
+        let strip = SanitizeOptions { spoiler_mode: SpoilerMode::Strip, ..Default::default() };
+        for html in [old, block, inline] {
+            assert_eq!(sanitize(html, strip), "допосле");
+        }
+        assert_eq!(
+            clean(block),
+            "до<div class=\"b-spoiler_block\"><span class=\"b-spoiler_label\">спойлер</span><div><div>тайна</div></div></div>после"
+        );
+    }
+
+    #[test]
+    fn spoiler_parts_are_not_spoilers() {
+        let strip = SanitizeOptions { spoiler_mode: SpoilerMode::Strip, ..Default::default() };
+        assert_eq!(sanitize("<span class=\"b-spoiler_label\">метка</span>", strip), "<span>метка</span>");
+        assert_eq!(sanitize("<div class=\"b-spoiler_body\">текст</div>", strip), "<div>текст</div>");
+    }
+}
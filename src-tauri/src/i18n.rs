@@ -0,0 +1,303 @@
+//! Строки, которые формирует бэкенд: меню трея, сообщения об ошибках,
+//! подстановки вроде «Неизвестно». Язык берётся из настройки `ui_language`.
+//!
+//! Строка ищется в каталоге текущего языка, затем в каталоге запасного
+//! (для английского это русский, основной язык приложения), и только потом
+//! возвращается сам ключ. Параметры подставляются вместо `{name}`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UiLanguage {
+    #[default]
+    Ru,
+    En,
+}
+
+type Catalog = &'static [(&'static str, &'static str)];
+
+const RU: Catalog = &[
+    ("tray.show", "Показать"),
+    ("tray.quit", "Выход"),
+    ("common.unknown", "Неизвестно"),
+    ("error.anime_not_found", "Аниме с ID {id} не найдено."),
+    ("error.manga_not_found", "Манга с ID {id} не найдена."),
+    ("error.character_not_found", "Персонаж с ID {id} не найден."),
+    ("error.profile_not_found", "Профиль «{name}» не найден."),
+    ("error.storage", "Ошибка доступа к данным приложения"),
+    ("error.task_failed", "Фоновая задача завершилась аварийно"),
+    ("error.channel", "Не удалось передать данные в окно"),
+    ("error.cancelled", "Запрос отменён"),
+    ("error.invalid_request", "Некорректные параметры запроса"),
+    ("error.network", "Ошибка сети, проверьте подключение"),
+    ("error.graphql", "Ошибка GraphQL API Shikimori"),
+    ("error.rate_limited", "Слишком много запросов, повторите позже"),
+    ("error.rate_limited_retry", "Слишком много запросов, повторите через {secs} с"),
+    ("error.http_status", "Сервер Shikimori ответил ошибкой {status}"),
+    ("error.invalid_response", "Не удалось разобрать ответ Shikimori"),
+    ("error.image_fetch", "Не удалось загрузить изображение"),
+    ("error.image_decode", "Не удалось декодировать изображение"),
+    ("validation.parameter", "Параметр {param}: {reason}"),
+    ("validation.role_requires_anime", "Параметр role используется только вместе с anime_id"),
+    ("validation.media_with_anime", "С anime_id нельзя фильтровать по is_manga, is_ranobe или is_anime=false"),
+    ("validation.limit_range", "Параметр limit должен быть от 1 до {max}"),
+    ("validation.people_page_cap", "Поиск людей отдаёт только первые {max} результатов, страница {page} за их пределами"),
+    ("validation.score_range", "Параметр score: оценка должна быть от 0 до 10"),
+    ("validation.saved_search_name", "Название сохранённого поиска не может быть пустым"),
+    ("validation.hex_color", "ожидается цвет вида #646cff"),
+    ("validation.settings_file", "Файл не похож на файл настроек Shikimore"),
+    ("validation.profile_name", "Имя профиля может содержать только буквы, цифры, пробел, - и _ (до 64 символов)"),
+    ("filter.invalid_value", "недопустимое значение \"{value}\", допустимы: {allowed}"),
+    ("filter.invalid_season", "недопустимый сезон \"{value}\", ожидается summer_2017, 2016, 2014_2016 или 199x"),
+    ("filter.season_range_reversed", "в диапазоне сезонов \"{value}\" начало больше конца"),
+    ("filter.min_greater_than_max", "минимум {min} больше максимума {max}"),
+    ("filter.year_out_of_range", "год {year} вне диапазона {min}–{max}"),
+    ("filter.invalid_genre_id", "недопустимый ID жанра \"{value}\""),
+    ("filter.genre_conflict", "Жанр {id} одновременно включён и исключён"),
+    ("filter_label.anime_kind.tv", "ТВ"),
+    ("filter_label.anime_kind.movie", "Фильм"),
+    ("filter_label.anime_kind.ova", "OVA"),
+    ("filter_label.anime_kind.ona", "ONA"),
+    ("filter_label.anime_kind.special", "Спешл"),
+    ("filter_label.anime_kind.tv_special", "ТВ-спешл"),
+    ("filter_label.anime_kind.music", "Клип"),
+    ("filter_label.anime_kind.pv", "Проморолик"),
+    ("filter_label.anime_kind.cm", "Реклама"),
+    ("filter_label.anime_kind.tv_13", "ТВ-13"),
+    ("filter_label.anime_kind.tv_24", "ТВ-24"),
+    ("filter_label.anime_kind.tv_48", "ТВ-48"),
+    ("filter_label.manga_kind.manga", "Манга"),
+    ("filter_label.manga_kind.manhwa", "Манхва"),
+    ("filter_label.manga_kind.manhua", "Маньхуа"),
+    ("filter_label.manga_kind.light_novel", "Ранобэ"),
+    ("filter_label.manga_kind.novel", "Новелла"),
+    ("filter_label.manga_kind.one_shot", "Ваншот"),
+    ("filter_label.manga_kind.doujin", "Додзинси"),
+    ("filter_label.anime_status.anons", "Анонс"),
+    ("filter_label.anime_status.ongoing", "Онгоинг"),
+    ("filter_label.anime_status.released", "Вышло"),
+    ("filter_label.manga_status.anons", "Анонс"),
+    ("filter_label.manga_status.ongoing", "Выходит"),
+    ("filter_label.manga_status.released", "Вышло"),
+    ("filter_label.manga_status.paused", "Приостановлено"),
+    ("filter_label.manga_status.discontinued", "Прекращено"),
+    ("filter_label.rating.none", "Без рейтинга"),
+    ("filter_label.rating.g", "G"),
+    ("filter_label.rating.pg", "PG"),
+    ("filter_label.rating.pg_13", "PG-13"),
+    ("filter_label.rating.r", "R-17"),
+    ("filter_label.rating.r_plus", "R+"),
+    ("filter_label.rating.rx", "Rx"),
+    ("filter_label.order.id", "По ID"),
+    ("filter_label.order.id_desc", "По ID (убывание)"),
+    ("filter_label.order.ranked", "По рейтингу"),
+    ("filter_label.order.kind", "По типу"),
+    ("filter_label.order.popularity", "По популярности"),
+    ("filter_label.order.name", "По названию"),
+    ("filter_label.order.aired_on", "По дате выхода"),
+    ("filter_label.order.episodes", "По числу эпизодов"),
+    ("filter_label.order.status", "По статусу"),
+    ("filter_label.order.random", "Случайно"),
+    ("filter_label.order.ranked_random", "Случайно среди рейтинговых"),
+    ("filter_label.order.ranked_shiki", "По рейтингу Shikimori"),
+    ("filter_label.order.created_at", "По дате добавления"),
+    ("filter_label.order.created_at_desc", "По дате добавления (убывание)"),
+    ("filter_label.season.winter", "Зима"),
+    ("filter_label.season.spring", "Весна"),
+    ("filter_label.season.summer", "Лето"),
+    ("filter_label.season.fall", "Осень"),
+    ("filter_label.character_role.main", "Главная роль"),
+    ("filter_label.character_role.supporting", "Второстепенная роль"),
+    ("html.spoiler", "спойлер"),
+];
+
+const EN: Catalog = &[
+    ("tray.show", "Show"),
+    ("tray.quit", "Quit"),
+    ("common.unknown", "Unknown"),
+    ("error.anime_not_found", "Anime with ID {id} not found."),
+    ("error.manga_not_found", "Manga with ID {id} not found."),
+    ("error.character_not_found", "Character with ID {id} not found."),
+    ("error.profile_not_found", "Profile \"{name}\" not found."),
+    ("error.storage", "Failed to access app data"),
+    ("error.task_failed", "Background task crashed"),
+    ("error.channel", "Failed to send data to the window"),
+    ("error.cancelled", "Request cancelled"),
+    ("error.invalid_request", "Invalid request parameters"),
+    ("error.network", "Network error, check your connection"),
+    ("error.graphql", "Shikimori GraphQL API error"),
+    ("error.rate_limited", "Too many requests, retry later"),
+    ("error.rate_limited_retry", "Too many requests, retry in {secs} s"),
+    ("error.http_status", "Shikimori server responded with error {status}"),
+    ("error.invalid_response", "Could not parse the Shikimori response"),
+    ("error.image_fetch", "Failed to load the image"),
+    ("error.image_decode", "Failed to decode the image"),
+    ("validation.parameter", "Parameter {param}: {reason}"),
+    ("validation.role_requires_anime", "Parameter role requires anime_id"),
+    ("validation.media_with_anime", "anime_id cannot be combined with is_manga, is_ranobe or is_anime=false"),
+    ("validation.limit_range", "Parameter limit must be between 1 and {max}"),
+    ("validation.people_page_cap", "People search only returns the first {max} results, page {page} is beyond them"),
+    ("validation.score_range", "Parameter score: must be between 0 and 10"),
+    ("validation.saved_search_name", "Saved search name cannot be empty"),
+    ("validation.hex_color", "expected a color like #646cff"),
+    ("validation.settings_file", "The file is not a Shikimore settings file"),
+    ("validation.profile_name", "Profile name may contain only letters, digits, space, - and _ (up to 64 characters)"),
+    ("filter.invalid_value", "invalid value \"{value}\", allowed: {allowed}"),
+    ("filter.invalid_season", "invalid season \"{value}\", expected summer_2017, 2016, 2014_2016 or 199x"),
+    ("filter.season_range_reversed", "season range \"{value}\" starts after it ends"),
+    ("filter.min_greater_than_max", "minimum {min} is greater than maximum {max}"),
+    ("filter.year_out_of_range", "year {year} is outside {min}–{max}"),
+    ("filter.invalid_genre_id", "invalid genre ID \"{value}\""),
+    ("filter.genre_conflict", "Genre {id} is both included and excluded"),
+    ("filter_label.anime_kind.tv", "TV"),
+    ("filter_label.anime_kind.movie", "Movie"),
+    ("filter_label.anime_kind.ova", "OVA"),
+    ("filter_label.anime_kind.ona", "ONA"),
+    ("filter_label.anime_kind.special", "Special"),
+    ("filter_label.anime_kind.tv_special", "TV special"),
+    ("filter_label.anime_kind.music", "Music video"),
+    ("filter_label.anime_kind.pv", "Promo"),
+    ("filter_label.anime_kind.cm", "Commercial"),
+    ("filter_label.anime_kind.tv_13", "TV-13"),
+    ("filter_label.anime_kind.tv_24", "TV-24"),
+    ("filter_label.anime_kind.tv_48", "TV-48"),
+    ("filter_label.manga_kind.manga", "Manga"),
+    ("filter_label.manga_kind.manhwa", "Manhwa"),
+    ("filter_label.manga_kind.manhua", "Manhua"),
+    ("filter_label.manga_kind.light_novel", "Light novel"),
+    ("filter_label.manga_kind.novel", "Novel"),
+    ("filter_label.manga_kind.one_shot", "One shot"),
+    ("filter_label.manga_kind.doujin", "Doujinshi"),
+    ("filter_label.anime_status.anons", "Announced"),
+    ("filter_label.anime_status.ongoing", "Ongoing"),
+    ("filter_label.anime_status.released", "Released"),
+    ("filter_label.manga_status.anons", "Announced"),
+    ("filter_label.manga_status.ongoing", "Publishing"),
+    ("filter_label.manga_status.released", "Released"),
+    ("filter_label.manga_status.paused", "Paused"),
+    ("filter_label.manga_status.discontinued", "Discontinued"),
+    ("filter_label.rating.none", "Unrated"),
+    ("filter_label.rating.g", "G"),
+    ("filter_label.rating.pg", "PG"),
+    ("filter_label.rating.pg_13", "PG-13"),
+    ("filter_label.rating.r", "R-17"),
+    ("filter_label.rating.r_plus", "R+"),
+    ("filter_label.rating.rx", "Rx"),
+    ("filter_label.order.id", "By ID"),
+    ("filter_label.order.id_desc", "By ID (descending)"),
+    ("filter_label.order.ranked", "By rating"),
+    ("filter_label.order.kind", "By type"),
+    ("filter_label.order.popularity", "By popularity"),
+    ("filter_label.order.name", "By title"),
+    ("filter_label.order.aired_on", "By release date"),
+    ("filter_label.order.episodes", "By episode count"),
+    ("filter_label.order.status", "By status"),
+    ("filter_label.order.random", "Random"),
+    ("filter_label.order.ranked_random", "Random among rated"),
+    ("filter_label.order.ranked_shiki", "By Shikimori rating"),
+    ("filter_label.order.created_at", "By date added"),
+    ("filter_label.order.created_at_desc", "By date added (descending)"),
+    ("filter_label.season.winter", "Winter"),
+    ("filter_label.season.spring", "Spring"),
+    ("filter_label.season.summer", "Summer"),
+    ("filter_label.season.fall", "Fall"),
+    ("filter_label.character_role.main", "Main role"),
+    ("filter_label.character_role.supporting", "Supporting role"),
+    ("html.spoiler", "spoiler"),
+];
+
+impl UiLanguage {
+    fn catalog(self) -> Catalog {
+        match self {
+            UiLanguage::Ru => RU,
+            UiLanguage::En => EN,
+        }
+    }
+
+    fn fallback(self) -> Option<UiLanguage> {
+        match self {
+            UiLanguage::Ru => None,
+            UiLanguage::En => Some(UiLanguage::Ru),
+        }
+    }
+
+    fn lookup(self, key: &str) -> Option<&'static str> {
+        self.catalog()
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, text)| *text)
+            .or_else(|| self.fallback().and_then(|f| f.lookup(key)))
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+pub fn current() -> UiLanguage {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => UiLanguage::En,
+        _ => UiLanguage::Ru,
+    }
+}
+
+pub fn set(language: UiLanguage) {
+    CURRENT.store(language as u8, Ordering::Relaxed);
+}
+
+/// Строка по ключу на текущем языке.
+pub fn t(key: &'static str) -> &'static str {
+    current().lookup(key).unwrap_or(key)
+}
+
+/// Строка по ключу с подстановкой параметров `{name}`.
+pub fn tf(key: &'static str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    args.iter()
+        .fold(t(key).to_string(), |text, (name, value)| text.replace(&format!("{{{}}}", name), &value.to_string()))
+}
+
+/// Все строки каталога на языке `language` с учётом запасного языка.
+pub fn translations(language: UiLanguage) -> BTreeMap<&'static str, &'static str> {
+    RU.iter()
+        .chain(EN)
+        .map(|(key, _)| (*key, language.lookup(key).unwrap_or(key)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = text.split('{').skip(1).filter_map(|part| part.split_once('}').map(|(name, _)| name)).collect();
+        names.sort_unstable();
+        names
+    }
+
+    #[test]
+    fn catalogs_have_the_same_keys_and_placeholders() {
+        for (key, ru) in RU {
+            let en = EN.iter().find(|(k, _)| k == key).map(|(_, text)| *text);
+            let en = en.unwrap_or_else(|| panic!("нет английской строки {}", key));
+            assert_eq!(placeholders(ru), placeholders(en), "{}", key);
+        }
+        assert_eq!(RU.len(), EN.len());
+    }
+
+    #[test]
+    fn lookup_uses_the_requested_language() {
+        assert_eq!(UiLanguage::En.lookup("tray.quit"), Some("Quit"));
+        assert_eq!(UiLanguage::Ru.lookup("tray.quit"), Some("Выход"));
+        assert_eq!(UiLanguage::En.lookup("no.such.key"), None);
+    }
+
+    #[test]
+    fn translations_cover_every_key() {
+        let en = translations(UiLanguage::En);
+        assert_eq!(en.len(), RU.len());
+        assert_eq!(en["html.spoiler"], "spoiler");
+    }
+}
@@ -3,43 +3,55 @@
 // #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use std::sync::{Mutex, OnceLock};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::Duration;
 use std::fs;
 use std::path::PathBuf;
 use serde::{Deserialize, Serialize};
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 use tauri_plugin_autostart::MacosLauncher;
-use shikicrate::{ShikicrateClient, ShikicrateError};
+use shikicrate::ShikicrateClient;
+
+mod batch;
+mod bbcode;
+mod error;
+mod filters;
+mod fuzzy;
+mod history;
+mod html;
+mod i18n;
+mod paging;
+mod prefetch;
+mod profiles;
+mod rate_limit;
+mod refill;
+mod requests;
+mod response_cache;
+mod saved_searches;
+mod search_all;
+mod settings;
+mod single_flight;
+mod spoilers;
+mod storage;
+mod titles;
+
+use batch::ByIdEntry;
+use bbcode::RichNode;
+use error::{ApiError, ErrorCode};
+use i18n::{t, tf, UiLanguage};
+use history::{ClickedResult, ContentType, HistoryEntry, SearchSuggestion};
+use html::SanitizeOptions;
+use prefetch::{PrefetchEntity, PrefetchPriority};
+use rate_limit::Priority;
+use saved_searches::{SavedSearch, SavedSearchParams};
+use search_all::{SearchAllEvent, SearchAllHit, SearchAllItem, SearchEntity};
+use settings::{AppSettings, SettingsStore};
+use spoilers::SpoilerMode;
+use titles::{Names, Titles};
 
 static ACCENT_CACHE: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
 static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct AppSettings {
-    pub theme: String,             // "dark", "light", "system"
-    pub nsfw: bool,
-    pub accent_color: String,
-    pub preferred_language: String, // "russian", "original"
-    pub view_mode: String,          // "grid", "list"
-    pub autostart: bool,
-    pub tray: bool,
-}
-
-impl Default for AppSettings {
-    fn default() -> Self {
-        Self {
-            theme: "dark".to_string(),
-            nsfw: false,
-            accent_color: "#646cff".to_string(),
-            preferred_language: "russian".to_string(),
-            view_mode: "grid".to_string(),
-            autostart: false,
-            tray: false,
-        }
-    }
-}
-
 fn get_config_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
     let path = app_handle.path().app_config_dir().map_err(|e| format!("Failed to get config dir: {}", e))?;
     if !path.exists() {
@@ -50,86 +62,126 @@ fn get_config_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
 
 #[tauri::command]
 fn get_settings(app_handle: tauri::AppHandle) -> AppSettings {
-    match get_config_path(&app_handle) {
-        Ok(path) => {
-            if path.exists() {
-                let content = fs::read_to_string(path).unwrap_or_default();
-                serde_json::from_str(&content).unwrap_or_else(|_| AppSettings::default())
-            } else {
-                AppSettings::default()
-            }
-        },
-        Err(e) => {
-            eprintln!("Error getting config path: {}", e);
-            AppSettings::default()
-        }
+    app_handle.state::<SettingsStore>().get()
+}
+
+#[tauri::command]
+fn update_settings(app_handle: tauri::AppHandle, settings: AppSettings) -> Result<(), ApiError> {
+    commit_settings(&app_handle, settings).map(|_| ())
+}
+
+/// Проверяет и сохраняет настройки, применяет их в бэкенде и рассылает окнам.
+fn commit_settings(app_handle: &tauri::AppHandle, settings: AppSettings) -> Result<AppSettings, ApiError> {
+    let change = app_handle.state::<SettingsStore>().update(settings)?;
+    apply_settings(app_handle, Some(&change.previous), &change.current);
+    if let Err(e) = app_handle.emit(settings::CHANGED_EVENT, &change.current) {
+        eprintln!("Failed to emit {}: {}", settings::CHANGED_EVENT, e);
+    }
+    Ok(change.current)
+}
+
+/// Автозапуск, которым при обычном сохранении управляет окно настроек.
+/// При импорте и смене профиля его нужно привести к новым настройкам здесь.
+fn apply_autostart(app_handle: &tauri::AppHandle, settings: &AppSettings) {
+    use tauri_plugin_autostart::ManagerExt;
+
+    let autolaunch = app_handle.autolaunch();
+    let result = if settings.autostart { autolaunch.enable() } else { autolaunch.disable() };
+    if let Err(e) = result {
+        eprintln!("Failed to update autostart: {}", e);
     }
 }
 
 #[tauri::command]
-fn update_settings(app_handle: tauri::AppHandle, settings: AppSettings) -> Result<(), String> {
-    let path = get_config_path(&app_handle)?;
-    let content = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
-    fs::write(path, content).map_err(|e| format!("Failed to write config: {}", e))?;
-    Ok(())
+fn export_settings(app_handle: tauri::AppHandle, path: String) -> Result<(), ApiError> {
+    settings::write_portable(&PathBuf::from(path), &get_settings(app_handle)).map_err(ApiError::storage)
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct ApiError {
-    kind: String,
-    message: String,
-    retry_after: Option<u64>,
-}
-
-impl std::fmt::Display for ApiError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: {}", self.kind, self.message)
-    }
-}
-
-impl std::error::Error for ApiError {}
-
-impl From<ShikicrateError> for ApiError {
-    fn from(err: ShikicrateError) -> Self {
-        match err {
-            ShikicrateError::Validation(msg) => ApiError {
-                kind: "validation".to_string(),
-                message: msg,
-                retry_after: None,
-            },
-            ShikicrateError::Http(e) => ApiError {
-                kind: "http".to_string(),
-                message: format!("Ошибка сети: {}", e),
-                retry_after: None,
-            },
-            ShikicrateError::GraphQL { message, .. } => ApiError {
-                kind: "graphql".to_string(),
-                message,
-                retry_after: None,
-            },
-            ShikicrateError::RateLimit { message, retry_after } => ApiError {
-                kind: "rate_limit".to_string(),
-                message,
-                retry_after,
-            },
-            ShikicrateError::Api { status, message } => ApiError {
-                kind: "api".to_string(),
-                message: format!("HTTP {}: {}", status, message),
-                retry_after: None,
-            },
-            ShikicrateError::Serialization(e) => ApiError {
-                kind: "serialization".to_string(),
-                message: format!("Ошибка сериализации: {}", e),
-                retry_after: None,
-            },
+#[tauri::command]
+fn import_settings(app_handle: tauri::AppHandle, path: String) -> Result<AppSettings, ApiError> {
+    let imported = settings::read_portable(&PathBuf::from(path))?;
+    let settings = commit_settings(&app_handle, imported)?;
+    apply_autostart(&app_handle, &settings);
+    Ok(settings)
+}
+
+fn get_profiles_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(get_config_path(app_handle)?.with_file_name(profiles::PROFILES_DIR))
+}
+
+#[tauri::command]
+fn list_profiles(app_handle: tauri::AppHandle) -> Result<Vec<String>, ApiError> {
+    Ok(profiles::list(&get_profiles_dir(&app_handle).map_err(ApiError::storage)?))
+}
+
+/// Сохраняет текущие настройки как профиль, перезаписывая одноимённый.
+#[tauri::command]
+fn save_profile(app_handle: tauri::AppHandle, name: String) -> Result<(), ApiError> {
+    let dir = get_profiles_dir(&app_handle).map_err(ApiError::storage)?;
+    profiles::save(&dir, &name, &get_settings(app_handle))
+}
+
+#[tauri::command]
+fn delete_profile(app_handle: tauri::AppHandle, name: String) -> Result<(), ApiError> {
+    profiles::remove(&get_profiles_dir(&app_handle).map_err(ApiError::storage)?, &name)
+}
+
+/// Заменяет текущие настройки профилем целиком: язык, трей и автозапуск
+/// перестраиваются сразу.
+#[tauri::command]
+fn switch_profile(app_handle: tauri::AppHandle, name: String) -> Result<AppSettings, ApiError> {
+    let profile = profiles::load(&get_profiles_dir(&app_handle).map_err(ApiError::storage)?, &name)?;
+    let settings = commit_settings(&app_handle, profile)?;
+    apply_autostart(&app_handle, &settings);
+    Ok(settings)
+}
+
+/// Подстраивает состояние бэкенда под настройки: язык сообщений и меню
+/// трея, выбор названий. `previous` — настройки до сохранения; при запуске
+/// их нет, и применяется всё.
+fn apply_settings(app_handle: &tauri::AppHandle, previous: Option<&AppSettings>, settings: &AppSettings) {
+    let language_changed = !matches!(previous, Some(p) if p.ui_language == settings.ui_language);
+    // Детали в кэше собраны с названиями, спойлерами и подписями по прежним настройкам
+    let details_changed = matches!(previous, Some(p) if p.preferred_language != settings.preferred_language
+        || p.spoiler_mode != settings.spoiler_mode
+        || p.ui_language != settings.ui_language);
+    i18n::set(settings.ui_language);
+    titles::set(settings.preferred_language);
+    if details_changed {
+        response_cache::clear();
+    }
+    if language_changed {
+        if let Some(tray) = app_handle.tray_by_id(TRAY_ID) {
+            if let Err(e) = tray_menu(app_handle).and_then(|menu| tray.set_menu(Some(menu))) {
+                eprintln!("Failed to rebuild tray menu: {}", e);
+            }
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+const TRAY_ID: &str = "main";
+
+/// Меню трея на текущем языке интерфейса.
+fn tray_menu(app_handle: &tauri::AppHandle) -> tauri::Result<tauri::menu::Menu<tauri::Wry>> {
+    use tauri::menu::{Menu, MenuItem};
+
+    let show_i = MenuItem::with_id(app_handle, "show", t("tray.show"), true, None::<&str>)?;
+    let quit_i = MenuItem::with_id(app_handle, "quit", t("tray.quit"), true, None::<&str>)?;
+    Menu::with_items(app_handle, &[&show_i, &quit_i])
+}
+
+/// Строки интерфейса, общие для бэкенда и окна, на выбранном или текущем языке.
+#[tauri::command]
+fn get_translations(language: Option<UiLanguage>) -> BTreeMap<&'static str, &'static str> {
+    i18n::translations(language.unwrap_or_else(i18n::current))
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct Anime {
     id: i64,
     title: String,
+    #[serde(flatten)]
+    titles: Titles,
     russian: Option<String>,
     url: Option<String>,
     poster_url: Option<String>,
@@ -140,10 +192,12 @@ struct Anime {
     episodes_aired: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct Manga {
     id: i64,
     title: String,
+    #[serde(flatten)]
+    titles: Titles,
     russian: Option<String>,
     url: Option<String>,
     poster_url: Option<String>,
@@ -154,10 +208,12 @@ struct Manga {
     chapters: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct Character {
     id: i64,
     name: String,
+    #[serde(flatten)]
+    titles: Titles,
     russian: Option<String>,
     url: Option<String>,
     poster_url: Option<String>,
@@ -167,10 +223,12 @@ struct Character {
     is_ranobe: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct CharacterDetail {
     id: i64,
     name: String,
+    #[serde(flatten)]
+    titles: Titles,
     russian: Option<String>,
     japanese: Option<String>,
     synonyms: Vec<String>,
@@ -178,11 +236,24 @@ struct CharacterDetail {
     poster_url: Option<String>,
     description: Option<String>,
     description_html: Option<String>,
+    description_rich: Option<Vec<RichNode>>,
     character_roles: Vec<CharacterRoleDetail>,
     seyus: Vec<Person>,
+    poster: Option<Poster>,
+    source: DetailSource,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Откуда взяты данные карточки персонажа.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum DetailSource {
+    Graphql,
+    Rest,
+    /// Основные поля из GraphQL, роли и сейю из REST.
+    Merged,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct CharacterRoleDetail {
     id: i64,
     roles_ru: Vec<String>,
@@ -190,10 +261,12 @@ struct CharacterRoleDetail {
     manga: Option<Manga>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct Person {
     id: i64,
     name: String,
+    #[serde(flatten)]
+    titles: Titles,
     russian: Option<String>,
     url: Option<String>,
     poster_url: Option<String>,
@@ -208,9 +281,28 @@ struct SearchResult<T> {
     items: Vec<T>,
     page: u32,
     limit: u32,
+    has_next_page: bool,
+    /// Общее число результатов, известное только на последней странице.
+    total: Option<u32>,
+    /// Запрос, по которому на самом деле искали, если исходный был в неверной раскладке.
+    corrected_query: Option<String>,
+}
+
+impl<T> SearchResult<T> {
+    fn new(items: Vec<T>, page: u32, limit: u32, has_next_page: bool) -> Self {
+        let total = (!has_next_page).then(|| page.saturating_sub(1) * limit + items.len() as u32);
+        SearchResult {
+            items,
+            page,
+            limit,
+            has_next_page,
+            total,
+            corrected_query: None,
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct Date {
     year: Option<i32>,
     month: Option<i32>,
@@ -218,28 +310,58 @@ struct Date {
     date: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct Genre {
     id: i64,
     name: String,
     russian: Option<String>,
     kind: Option<String>,
+    label: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct GenreGroups {
+    genres: Vec<Genre>,
+    themes: Vec<Genre>,
+    demographics: Vec<Genre>,
+}
+
+impl GenreGroups {
+    fn push(&mut self, genre: Genre) {
+        match genre.kind.as_deref() {
+            Some("theme") => self.themes.push(genre),
+            Some("demographic") => self.demographics.push(genre),
+            _ => self.genres.push(genre),
+        }
+    }
+
+    fn sort(&mut self) {
+        for list in [&mut self.genres, &mut self.themes, &mut self.demographics] {
+            list.sort_by(|a, b| a.label.cmp(&b.label));
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct GenreCatalog {
+    anime: GenreGroups,
+    manga: GenreGroups,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct Studio {
     id: i64,
     name: String,
     image_url: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct Publisher {
     id: i64,
     name: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct ExternalLink {
     id: Option<i64>,
     kind: String,
@@ -248,7 +370,7 @@ struct ExternalLink {
     updated_at: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct PersonRole {
     id: i64,
     roles_ru: Option<Vec<String>>,
@@ -256,7 +378,7 @@ struct PersonRole {
     person: Person,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct CharacterRole {
     id: i64,
     roles_ru: Option<Vec<String>>,
@@ -264,7 +386,7 @@ struct CharacterRole {
     character: Character,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct Poster {
     main: Option<String>,
     original: Option<String>,
@@ -273,7 +395,30 @@ struct Poster {
     x48: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Размеры постера, которые запрашивают карточки.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PosterSize {
+    Preview,
+    Main,
+}
+
+impl Poster {
+    /// URL нужного размера; если его нет, ближайший больший, затем ближайший меньший.
+    fn url(&self, size: PosterSize) -> Option<String> {
+        // Все размеры постера от меньшего к большему
+        let sizes = [&self.x48, &self.x96, &self.preview, &self.main, &self.original];
+        let wanted = match size {
+            PosterSize::Preview => 2,
+            PosterSize::Main => 3,
+        };
+        sizes[wanted..]
+            .iter()
+            .chain(sizes[..wanted].iter().rev())
+            .find_map(|url| (*url).clone())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct RelatedAnime {
     id: Option<i64>,
     name: Option<String>,
@@ -281,7 +426,7 @@ struct RelatedAnime {
     image: Option<Poster>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct RelatedManga {
     id: Option<i64>,
     name: Option<String>,
@@ -289,16 +434,18 @@ struct RelatedManga {
     image: Option<Poster>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct Related {
     id: i64,
+    #[serde(flatten)]
+    titles: Titles,
     anime: Option<RelatedAnime>,
     manga: Option<RelatedManga>,
     relation_kind: String,
     relation_text: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct Video {
     id: i64,
     url: Option<String>,
@@ -308,7 +455,7 @@ struct Video {
     image_url: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct Screenshot {
     id: i64,
     original_url: Option<String>,
@@ -316,23 +463,25 @@ struct Screenshot {
     x332_url: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct ScoreStat {
     score: i32,
     count: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct StatusStat {
     status: String,
     count: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct AnimeDetail {
     id: i64,
     mal_id: Option<i64>,
     title: String,
+    #[serde(flatten)]
+    titles: Titles,
     russian: Option<String>,
     license_name_ru: Option<String>,
     english: Option<String>,
@@ -342,6 +491,7 @@ struct AnimeDetail {
     poster_url: Option<String>,
     description: Option<String>,
     description_html: Option<String>,
+    description_rich: Option<Vec<RichNode>>,
     description_source: Option<String>,
     score: Option<f64>,
     kind: Option<String>,
@@ -370,11 +520,13 @@ struct AnimeDetail {
     licensors: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct MangaDetail {
     id: i64,
     mal_id: Option<i64>,
     title: String,
+    #[serde(flatten)]
+    titles: Titles,
     russian: Option<String>,
     license_name_ru: Option<String>,
     english: Option<String>,
@@ -384,6 +536,7 @@ struct MangaDetail {
     poster_url: Option<String>,
     description: Option<String>,
     description_html: Option<String>,
+    description_rich: Option<Vec<RichNode>>,
     description_source: Option<String>,
     score: Option<f64>,
     kind: Option<String>,
@@ -404,18 +557,52 @@ struct MangaDetail {
     licensors: Option<Vec<String>>,
 }
 
+/// Тяжёлая часть детальной карточки, которая приходит отдельным сообщением.
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "section", content = "data", rename_all = "snake_case")]
+enum DetailSection {
+    CharacterRoles(Vec<CharacterRole>),
+    PersonRoles(Vec<PersonRole>),
+    Related(Vec<Related>),
+    Screenshots(Vec<Screenshot>),
+    ExternalLinks(Vec<ExternalLink>),
+}
+
+/// Сообщения потоковой загрузки деталей: сначала основные поля без тяжёлых
+/// разделов, затем разделы по одному.
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+enum DetailEvent<T> {
+    Core(T),
+    Section(DetailSection),
+    Done,
+}
+
 // REST API structures for character details
 #[derive(Debug, Deserialize)]
 struct RestImage {
     original: Option<String>,
-    #[allow(dead_code)]
     preview: Option<String>,
-    #[allow(dead_code)]
     x96: Option<String>,
-    #[allow(dead_code)]
     x48: Option<String>,
 }
 
+impl From<RestImage> for Poster {
+    fn from(image: RestImage) -> Self {
+        Poster {
+            main: None,
+            original: fix_url(image.original),
+            preview: fix_url(image.preview),
+            x96: fix_url(image.x96),
+            x48: fix_url(image.x48),
+        }
+    }
+}
+
+fn rest_poster_url(image: Option<RestImage>, size: PosterSize) -> Option<String> {
+    image.and_then(|image| Poster::from(image).url(size))
+}
+
 fn deser_id<'de, D>(deserializer: D) -> Result<i64, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -493,9 +680,17 @@ struct RestManga {
 }
 
 #[derive(Debug, Deserialize)]
-struct RestCharacter {
+struct RestGenre {
     #[serde(deserialize_with = "deser_id")]
     id: i64,
+    name: String,
+    russian: Option<String>,
+    kind: Option<String>,
+    entry_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RestCharacter {
     name: Option<String>,
     russian: Option<String>,
     japanese: Option<String>,
@@ -514,301 +709,927 @@ struct RestCharacter {
     mangas: Vec<RestManga>,
 }
 
+impl RestCharacter {
+    /// Пустая запись, когда REST не ответил и всё берётся из GraphQL.
+    fn empty() -> Self {
+        RestCharacter {
+            name: None,
+            russian: None,
+            japanese: None,
+            altname: None,
+            image: None,
+            url: None,
+            description: None,
+            description_html: None,
+            description_source: None,
+            seyu: Vec::new(),
+            animes: Vec::new(),
+            mangas: Vec::new(),
+        }
+    }
+}
 
 
-#[tauri::command]
-async fn search_anime(
-    app_handle: tauri::AppHandle,
-    query: String,
-    ids: Option<String>,
-    page: Option<u32>,
-    limit: Option<u32>,
-    kind: Option<String>,
-    status: Option<String>,
-    season: Option<String>,
-    rating: Option<String>,
-    genre: Option<String>,
-    studio: Option<String>,
-    order: Option<String>,
-) -> Result<SearchResult<Anime>, ApiError> {
-    println!(">>> [Backend] search_anime вызвана: query='{}', ids={:?}, page={:?}, limit={:?}, kind={:?}, status={:?}, studio={:?}, order={:?}", query, ids, page, limit, kind, status, studio, order);
-    
-    let settings = get_settings(app_handle);
-    let page = page.unwrap_or(1);
-    let limit = limit.unwrap_or(20);
+// REST API structures for streaming detail cards: core fields without roles and related
+#[derive(Debug, Deserialize)]
+struct RestStudio {
+    #[serde(deserialize_with = "deser_id")]
+    id: i64,
+    name: String,
+    image: Option<String>,
+}
 
-    println!(">>> [Backend] Создание клиента...");
-    let client = match ShikicrateClient::new() {
-        Ok(c) => {
-            println!(">>> [Backend] Клиент создан успешно");
-            c
-        },
-        Err(e) => {
-            println!(">>> [Backend] Ошибка создания клиента: {:?}", e);
-            return Err(ApiError::from(e));
-        }
-    };
-    
-    use shikicrate::queries::AnimeSearchParams;
-    
-    let params = AnimeSearchParams {
-        search: if query.is_empty() { None } else { Some(query.clone()) },
-        ids,
-        limit: Some(limit as i32),
-        page: Some(page as i32),
-        kind,
-        status,
-        season,
-        rating,
-        genre,
-        studio,
-        order,
-        censored: Some(!settings.nsfw),
-    };
-    
-    println!(">>> [Backend] Выполнение запроса к API...");
-    let animes = match client.animes(params).await {
-        Ok(a) => {
-            println!(">>> [Backend] Получено {} аниме", a.len());
-            a
-        },
-        Err(e) => {
-            println!(">>> [Backend] Ошибка запроса аниме: {:?}", e);
-            let api_err = ApiError::from(e);
-            println!(">>> [Backend] Преобразованная ошибка: kind={}, message={}", api_err.kind, api_err.message);
-            return Err(api_err);
-        }
-    };
-    
-    println!(">>> [Backend] Преобразование данных...");
-    let anime_list: Vec<Anime> = animes
-        .into_iter()
-        .map(|a| Anime {
-            id: a.id,
-            title: a.name,
-            russian: a.russian,
-            url: a.url.or_else(|| Some(format!("https://shikimori.one/animes/{}", a.id))),
-            poster_url: a.poster.and_then(|p| p.main_url),
-            score: a.score,
-            kind: a.kind,
-            status: a.status,
-            episodes: a.episodes,
-            episodes_aired: a.episodes_aired,
-        })
-        .collect();
-    
-    println!(">>> [Backend] Возврат результата: {} элементов", anime_list.len());
-    Ok(SearchResult {
-        items: anime_list,
-        page,
-        limit,
-    })
+#[derive(Debug, Deserialize)]
+struct RestPublisher {
+    #[serde(deserialize_with = "deser_id")]
+    id: i64,
+    name: String,
 }
 
-#[tauri::command]
-async fn search_anime_lite(
-    app_handle: tauri::AppHandle,
-    query: String,
+#[derive(Debug, Deserialize)]
+struct RestVideo {
+    #[serde(deserialize_with = "deser_id")]
+    id: i64,
+    url: Option<String>,
+    name: Option<String>,
     kind: Option<String>,
-    status: Option<String>,
-    genre: Option<String>,
-    studio: Option<String>,
-    limit: Option<u32>,
-) -> Result<Vec<Anime>, ApiError> {
-    let settings = get_settings(app_handle);
-    let client = ShikicrateClient::new().map_err(ApiError::from)?;
-    let limit = limit.unwrap_or(50);
-    
-    use shikicrate::queries::AnimeSearchParams;
-    let params = AnimeSearchParams {
-        search: if query.is_empty() { None } else { Some(query) },
-        ids: None,
-        limit: Some(limit as i32),
-        page: Some(1),
-        kind,
-        status,
-        genre,
-        studio,
-        censored: Some(!settings.nsfw),
-        ..Default::default()
-    };
-    
-    let animes = client.animes_lite(params).await.map_err(ApiError::from)?;
-    Ok(animes.into_iter().map(|a| Anime {
-        id: a.id,
-        title: a.name,
-        russian: a.russian,
-        url: None,
-        poster_url: None,
-        score: None,
-        kind: None,
-        status: None,
-        episodes: None,
-        episodes_aired: None,
-    }).collect())
+    player_url: Option<String>,
+    image_url: Option<String>,
 }
 
-#[tauri::command]
-async fn search_manga(
-    app_handle: tauri::AppHandle,
-    query: String,
-    ids: Option<String>,
-    page: Option<u32>,
-    limit: Option<u32>,
-    kind: Option<String>,
-    status: Option<String>,
-    genre: Option<String>,
-    publisher: Option<String>,
-    order: Option<String>,
-) -> Result<SearchResult<Manga>, ApiError> {
-    let settings = get_settings(app_handle);
-    let page = page.unwrap_or(1);
-    let limit = limit.unwrap_or(20);
+#[derive(Debug, Deserialize)]
+struct RestScoreStat {
+    name: i32,
+    value: i32,
+}
 
-    let client = ShikicrateClient::new().map_err(ApiError::from)?;
-    
-    use shikicrate::queries::MangaSearchParams;
-    
-    let params = MangaSearchParams {
-        search: if query.is_empty() { None } else { Some(query) },
-        ids,
-        limit: Some(limit as i32),
-        page: Some(page as i32),
-        kind,
-        status,
-        genre,
-        publisher,
-        order,
-        censored: Some(!settings.nsfw),
-    };
-    
-    let mangas = client.mangas(params).await.map_err(ApiError::from)?;
-    
-    let manga_list: Vec<Manga> = mangas
-        .into_iter()
-        .map(|m| Manga {
-            id: m.id,
-            title: m.name,
-            russian: m.russian,
-            url: m.url.or_else(|| Some(format!("https://shikimori.one/mangas/{}", m.id))),
-            poster_url: m.poster.and_then(|p| p.main_url),
-            score: m.score,
-            kind: m.kind,
-            status: m.status,
-            volumes: m.volumes,
-            chapters: m.chapters,
-        })
-        .collect();
-    
-    Ok(SearchResult {
-        items: manga_list,
-        page,
-        limit,
-    })
+#[derive(Debug, Deserialize)]
+struct RestStatusStat {
+    name: String,
+    value: i32,
 }
 
-#[tauri::command]
-async fn search_characters(
-    query: String,
+#[derive(Debug, Deserialize)]
+struct RestAnimeDetail {
+    #[serde(deserialize_with = "deser_id")]
+    id: i64,
+    myanimelist_id: Option<i64>,
+    name: String,
+    russian: Option<String>,
+    license_name_ru: Option<String>,
+    #[serde(default)]
+    english: Vec<Option<String>>,
+    #[serde(default)]
+    japanese: Vec<Option<String>>,
+    synonyms: Option<Vec<String>>,
+    image: Option<RestImage>,
+    url: Option<String>,
+    kind: Option<String>,
+    #[serde(default, deserialize_with = "deser_score")]
+    score: Option<f64>,
+    status: Option<String>,
+    rating: Option<String>,
+    episodes: Option<i32>,
+    episodes_aired: Option<i32>,
+    duration: Option<i32>,
+    aired_on: Option<String>,
+    released_on: Option<String>,
+    next_episode_at: Option<String>,
+    description: Option<String>,
+    description_html: Option<String>,
+    description_source: Option<String>,
+    #[serde(default)]
+    genres: Vec<RestGenre>,
+    #[serde(default)]
+    studios: Vec<RestStudio>,
+    #[serde(default)]
+    videos: Vec<RestVideo>,
+    #[serde(default)]
+    rates_scores_stats: Vec<RestScoreStat>,
+    #[serde(default)]
+    rates_statuses_stats: Vec<RestStatusStat>,
+    fansubbers: Option<Vec<String>>,
+    fandubbers: Option<Vec<String>>,
+    licensors: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RestMangaDetail {
+    #[serde(deserialize_with = "deser_id")]
+    id: i64,
+    myanimelist_id: Option<i64>,
+    name: String,
+    russian: Option<String>,
+    license_name_ru: Option<String>,
+    #[serde(default)]
+    english: Vec<Option<String>>,
+    #[serde(default)]
+    japanese: Vec<Option<String>>,
+    synonyms: Option<Vec<String>>,
+    image: Option<RestImage>,
+    url: Option<String>,
+    kind: Option<String>,
+    #[serde(default, deserialize_with = "deser_score")]
+    score: Option<f64>,
+    status: Option<String>,
+    volumes: Option<i32>,
+    chapters: Option<i32>,
+    aired_on: Option<String>,
+    released_on: Option<String>,
+    description: Option<String>,
+    description_html: Option<String>,
+    description_source: Option<String>,
+    #[serde(default)]
+    genres: Vec<RestGenre>,
+    #[serde(default)]
+    publishers: Vec<RestPublisher>,
+    #[serde(default)]
+    rates_scores_stats: Vec<RestScoreStat>,
+    #[serde(default)]
+    rates_statuses_stats: Vec<RestStatusStat>,
+    licensors: Option<Vec<String>>,
+}
+
+/// Персонаж или человек в ответе `/roles`.
+#[derive(Debug, Deserialize)]
+struct RestRoleEntry {
+    #[serde(deserialize_with = "deser_id")]
+    id: i64,
+    name: String,
+    russian: Option<String>,
+    image: Option<RestImage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RestRole {
+    #[serde(default)]
+    roles: Vec<String>,
+    #[serde(default)]
+    roles_russian: Vec<String>,
+    character: Option<RestRoleEntry>,
+    person: Option<RestRoleEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RestRelatedEntry {
+    #[serde(deserialize_with = "deser_id")]
+    id: i64,
+    name: Option<String>,
+    russian: Option<String>,
+    image: Option<RestImage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RestRelated {
+    relation: String,
+    relation_russian: Option<String>,
+    anime: Option<RestRelatedEntry>,
+    manga: Option<RestRelatedEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RestScreenshot {
+    original: Option<String>,
+    preview: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RestExternalLink {
+    id: Option<i64>,
+    kind: String,
+    url: String,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+}
+
+/// Параметры поиска аниме: команда `search_anime`, пакетные запросы по ID,
+/// общий поиск и сохранённые поиски.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct AnimeSearch {
+    query: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ids: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<u32>,
+    kind: Option<String>,
+    status: Option<String>,
+    season: Option<String>,
+    rating: Option<String>,
+    genre: Option<String>,
+    genre_include: Option<Vec<i64>>,
+    genre_exclude: Option<Vec<i64>>,
+    studio: Option<String>,
+    order: Option<String>,
+    score_min: Option<f64>,
+    year_from: Option<u16>,
+    year_to: Option<u16>,
+    episodes_min: Option<i32>,
+    episodes_max: Option<i32>,
+    duration_min: Option<i32>,
+    duration_max: Option<i32>,
+}
+
+/// Окно передаёт поля [`AnimeSearch`] отдельными аргументами.
+#[tauri::command]
+async fn search_anime(
+    app_handle: tauri::AppHandle,
+    query: String,
+    ids: Option<String>,
+    page: Option<u32>,
+    limit: Option<u32>,
+    kind: Option<String>,
+    status: Option<String>,
+    season: Option<String>,
+    rating: Option<String>,
+    genre: Option<String>,
+    genre_include: Option<Vec<i64>>,
+    genre_exclude: Option<Vec<i64>>,
+    studio: Option<String>,
+    order: Option<String>,
+    score_min: Option<f64>,
+    year_from: Option<u16>,
+    year_to: Option<u16>,
+    episodes_min: Option<i32>,
+    episodes_max: Option<i32>,
+    duration_min: Option<i32>,
+    duration_max: Option<i32>,
+    request_token: Option<String>,
+) -> Result<SearchResult<Anime>, ApiError> {
+    let search = AnimeSearch {
+        query, ids, page, limit, kind, status, season, rating, genre, genre_include, genre_exclude,
+        studio, order, score_min, year_from, year_to, episodes_min, episodes_max, duration_min,
+        duration_max,
+    };
+    requests::run(request_token, run_anime_search(app_handle, search, Priority::Foreground)).await
+}
+
+async fn run_anime_search(app_handle: tauri::AppHandle, search: AnimeSearch, priority: Priority) -> Result<SearchResult<Anime>, ApiError> {
+    let AnimeSearch {
+        query, ids, page, limit, kind, status, season, rating, genre, genre_include, genre_exclude, studio, order,
+        score_min, year_from, year_to, episodes_min, episodes_max, duration_min, duration_max,
+    } = search;
+    println!(">>> [Backend] search_anime вызвана: query='{}', ids={:?}, page={:?}, limit={:?}, kind={:?}, status={:?}, studio={:?}, order={:?}", query, ids, page, limit, kind, status, studio, order);
+
+    use filters::{AnimeKind, AnimeStatus, NumericRange, Rating, Season};
+
+    let kind = filters::validate_list::<AnimeKind>("kind", kind).map_err(ApiError::validation)?;
+    let status = filters::validate_list::<AnimeStatus>("status", status).map_err(ApiError::validation)?;
+    let season = filters::validate_list::<Season>("season", season).map_err(ApiError::validation)?;
+    let rating = filters::validate_list::<Rating>("rating", rating).map_err(ApiError::validation)?;
+    let genre = filters::build_genre_filter(
+        genre,
+        genre_include.as_deref().unwrap_or_default(),
+        genre_exclude.as_deref().unwrap_or_default(),
+    )
+    .map_err(ApiError::validation)?;
+    let order = filters::validate_order(order).map_err(ApiError::validation)?;
+
+    let score = NumericRange::new(score_min, None);
+    let years = NumericRange::new(year_from, year_to);
+    let episodes = NumericRange::new(episodes_min, episodes_max);
+    let duration = NumericRange::new(duration_min, duration_max);
+    validate_score(score)?;
+    years.validate_years("year").map_err(ApiError::validation)?;
+    episodes.validate("episodes").map_err(ApiError::validation)?;
+    duration.validate("duration").map_err(ApiError::validation)?;
+
+    // Диапазон лет передаём в API как season=2015_2020, если сезон не задан явно,
+    // иначе проверяем год выхода сами
+    let (season, years_post) = match season {
+        None => (years.as_season().map(|s| s.to_string()), NumericRange::default()),
+        Some(season) => (Some(season), years),
+    };
+    let post_filtered = score.is_set() || years_post.is_set() || episodes.is_set() || duration.is_set();
+
+    let settings = get_settings(app_handle);
+    let page = page.unwrap_or(1);
+    let limit = limit.unwrap_or(20);
+
+    println!(">>> [Backend] Создание клиента...");
+    let client = match ShikicrateClient::new() {
+        Ok(c) => {
+            println!(">>> [Backend] Клиент создан успешно");
+            c
+        },
+        Err(e) => {
+            println!(">>> [Backend] Ошибка создания клиента: {:?}", e);
+            return Err(ApiError::from(e));
+        }
+    };
+
+    use shikicrate::queries::AnimeSearchParams;
+
+    // Пустую выдачу по запросу в неверной раскладке повторяем в другой: "yfhenj" -> "наруто"
+    let mut search = query;
+    let mut corrected_query = None;
+    let anime_list = loop {
+        let make_params = |page: u32, limit: u32| AnimeSearchParams {
+            search: if search.is_empty() { None } else { Some(search.clone()) },
+            ids: ids.clone(),
+            limit: Some(limit as i32),
+            page: Some(page as i32),
+            kind: kind.clone(),
+            status: status.clone(),
+            season: season.clone(),
+            rating: rating.clone(),
+            genre: genre.clone(),
+            studio: studio.clone(),
+            order: order.clone(),
+            censored: Some(!settings.nsfw),
+        };
+
+        // Минимальную оценку, число эпизодов и длительность shikicrate не принимает,
+        // поэтому отбираем их сами; отброшенные элементы приходят как None
+        let client = &client;
+        let make_params = &make_params;
+        let search_text = search.as_str();
+        let fetch = |api_page: u32, api_limit: u32| async move {
+            println!(">>> [Backend] Выполнение запроса к API (страница {})...", api_page);
+            let animes = requests::send_with(priority, client.animes(make_params(api_page, api_limit))).await.inspect_err(|e| {
+                println!(">>> [Backend] Ошибка запроса аниме: {}", e);
+            })?;
+            println!(">>> [Backend] Получено {} аниме", animes.len());
+
+            Ok(animes
+                .into_iter()
+                .map(|a| {
+                    let keep = score.contains(a.score)
+                        && years_post.contains(a.aired_on.as_ref().and_then(|d| d.year).map(|y| y as u16))
+                        && episodes.contains(a.episodes.filter(|e| *e > 0))
+                        && duration.contains(a.duration);
+                    if !keep {
+                        return None;
+                    }
+                    let names = [&a.english, &a.japanese, &a.license_name_ru, &a.russian]
+                        .into_iter()
+                        .flatten()
+                        .chain(a.synonyms.iter().flatten())
+                        .map(String::as_str);
+                    let similarity = fuzzy::score(search_text, std::iter::once(a.name.as_str()).chain(names));
+                    Some((similarity, Anime {
+                        id: a.id,
+                        titles: Names {
+                            title: &a.name,
+                            russian: a.russian.as_deref(),
+                            license_name_ru: a.license_name_ru.as_deref(),
+                            english: a.english.as_deref(),
+                            japanese: a.japanese.as_deref(),
+                        }
+                        .titles(),
+                        title: a.name,
+                        russian: a.russian,
+                        url: a.url.or_else(|| Some(format!("https://shikimori.one/animes/{}", a.id))),
+                        poster_url: a.poster.and_then(|p| p.main_url),
+                        score: a.score,
+                        kind: a.kind,
+                        status: a.status,
+                        episodes: a.episodes,
+                        episodes_aired: a.episodes_aired,
+                    }))
+                })
+                .collect::<Vec<_>>())
+        };
+
+        let (ranked, has_next_page): (Vec<(f64, Anime)>, bool) = if post_filtered {
+            let key = format!(
+                "anime:{}:{:?}:{}:{:?}:{:?}:{:?}:{:?}:{:?}:{:?}:{:?}:{:?}:{:?}:{:?}:{:?}:{}",
+                search, ids, limit, kind, status, season, rating, genre, studio, order,
+                score, years_post, episodes, duration, settings.nsfw
+            );
+            refill::filtered_page(&key, page, limit, |api_page| fetch(api_page, limit)).await?
+        } else {
+            let window = paging::window(page, limit);
+            let items = fetch(window.api_page, window.api_limit).await?;
+            let (items, has_next_page) = window.split(items, limit);
+            (items.into_iter().flatten().collect(), has_next_page)
+        };
+
+        if ranked.is_empty() && page == 1 && ids.is_none() && corrected_query.is_none() {
+            if let Some(switched) = fuzzy::switch_layout(&search) {
+                println!(">>> [Backend] Пустая выдача, повтор в другой раскладке: '{}'", switched);
+                corrected_query = Some(switched.clone());
+                search = switched;
+                continue;
+            }
+        }
+
+        // Явную сортировку не трогаем, переранжируем только выдачу по релевантности
+        let items = if order.is_none() && !search.is_empty() {
+            fuzzy::rerank(ranked)
+        } else {
+            ranked.into_iter().map(|(_, anime)| anime).collect()
+        };
+        break SearchResult::new(items, page, limit, has_next_page);
+    };
+
+    println!(">>> [Backend] Возврат результата: {} элементов", anime_list.items.len());
+    Ok(SearchResult { corrected_query, ..anime_list })
+}
+
+#[tauri::command]
+async fn search_anime_lite(
+    app_handle: tauri::AppHandle,
+    query: String,
+    kind: Option<String>,
+    status: Option<String>,
+    genre: Option<String>,
+    studio: Option<String>,
+    limit: Option<u32>,
+    request_token: Option<String>,
+) -> Result<Vec<Anime>, ApiError> {
+    requests::run(request_token, async move {
+        use filters::{AnimeKind, AnimeStatus};
+
+        let kind = filters::validate_list::<AnimeKind>("kind", kind).map_err(ApiError::validation)?;
+        let status = filters::validate_list::<AnimeStatus>("status", status).map_err(ApiError::validation)?;
+
+        let settings = get_settings(app_handle);
+        let client = ShikicrateClient::new().map_err(ApiError::from)?;
+        let limit = limit.unwrap_or(50);
+    
+        use shikicrate::queries::AnimeSearchParams;
+        let params = AnimeSearchParams {
+            search: if query.is_empty() { None } else { Some(query) },
+            ids: None,
+            limit: Some(limit as i32),
+            page: Some(1),
+            kind,
+            status,
+            genre,
+            studio,
+            censored: Some(!settings.nsfw),
+            ..Default::default()
+        };
+    
+        let animes = requests::send(client.animes_lite(params)).await?;
+        Ok(animes.into_iter().map(|a| Anime {
+            id: a.id,
+            titles: Names { title: &a.name, russian: a.russian.as_deref(), ..Default::default() }.titles(),
+            title: a.name,
+            russian: a.russian,
+            url: None,
+            poster_url: None,
+            score: None,
+            kind: None,
+            status: None,
+            episodes: None,
+            episodes_aired: None,
+        }).collect())
+    })
+    .await
+}
+
+/// Параметры поиска манги, как [`AnimeSearch`] для аниме.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct MangaSearch {
+    query: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ids: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<u32>,
+    kind: Option<String>,
+    status: Option<String>,
+    genre: Option<String>,
+    genre_include: Option<Vec<i64>>,
+    genre_exclude: Option<Vec<i64>>,
+    publisher: Option<String>,
+    order: Option<String>,
+    score_min: Option<f64>,
+    year_from: Option<u16>,
+    year_to: Option<u16>,
+    volumes_min: Option<i32>,
+    volumes_max: Option<i32>,
+    chapters_min: Option<i32>,
+    chapters_max: Option<i32>,
+}
+
+/// Окно передаёт поля [`MangaSearch`] отдельными аргументами.
+#[tauri::command]
+async fn search_manga(
+    app_handle: tauri::AppHandle,
+    query: String,
+    ids: Option<String>,
+    page: Option<u32>,
+    limit: Option<u32>,
+    kind: Option<String>,
+    status: Option<String>,
+    genre: Option<String>,
+    genre_include: Option<Vec<i64>>,
+    genre_exclude: Option<Vec<i64>>,
+    publisher: Option<String>,
+    order: Option<String>,
+    score_min: Option<f64>,
+    year_from: Option<u16>,
+    year_to: Option<u16>,
+    volumes_min: Option<i32>,
+    volumes_max: Option<i32>,
+    chapters_min: Option<i32>,
+    chapters_max: Option<i32>,
+    request_token: Option<String>,
+) -> Result<SearchResult<Manga>, ApiError> {
+    let search = MangaSearch {
+        query, ids, page, limit, kind, status, genre, genre_include, genre_exclude, publisher, order,
+        score_min, year_from, year_to, volumes_min, volumes_max, chapters_min, chapters_max,
+    };
+    requests::run(request_token, run_manga_search(app_handle, search, Priority::Foreground)).await
+}
+
+async fn run_manga_search(app_handle: tauri::AppHandle, search: MangaSearch, priority: Priority) -> Result<SearchResult<Manga>, ApiError> {
+    let MangaSearch {
+        query, ids, page, limit, kind, status, genre, genre_include, genre_exclude, publisher, order,
+        score_min, year_from, year_to, volumes_min, volumes_max, chapters_min, chapters_max,
+    } = search;
+    use filters::{MangaKind, MangaStatus, NumericRange};
+
+    let kind = filters::validate_list::<MangaKind>("kind", kind).map_err(ApiError::validation)?;
+    let status = filters::validate_list::<MangaStatus>("status", status).map_err(ApiError::validation)?;
+    let genre = filters::build_genre_filter(
+        genre,
+        genre_include.as_deref().unwrap_or_default(),
+        genre_exclude.as_deref().unwrap_or_default(),
+    )
+    .map_err(ApiError::validation)?;
+    let order = filters::validate_order(order).map_err(ApiError::validation)?;
+
+    let score = NumericRange::new(score_min, None);
+    let years = NumericRange::new(year_from, year_to);
+    let volumes = NumericRange::new(volumes_min, volumes_max);
+    let chapters = NumericRange::new(chapters_min, chapters_max);
+    validate_score(score)?;
+    years.validate_years("year").map_err(ApiError::validation)?;
+    volumes.validate("volumes").map_err(ApiError::validation)?;
+    chapters.validate("chapters").map_err(ApiError::validation)?;
+    let post_filtered = score.is_set() || years.is_set() || volumes.is_set() || chapters.is_set();
+
+    let settings = get_settings(app_handle);
+    let page = page.unwrap_or(1);
+    let limit = limit.unwrap_or(20);
+
+    let client = ShikicrateClient::new().map_err(ApiError::from)?;
+
+    use shikicrate::queries::MangaSearchParams;
+
+    let mut search = query;
+    let mut corrected_query = None;
+    let manga_list = loop {
+        let make_params = |page: u32, limit: u32| MangaSearchParams {
+            search: if search.is_empty() { None } else { Some(search.clone()) },
+            ids: ids.clone(),
+            limit: Some(limit as i32),
+            page: Some(page as i32),
+            kind: kind.clone(),
+            status: status.clone(),
+            genre: genre.clone(),
+            publisher: publisher.clone(),
+            order: order.clone(),
+            censored: Some(!settings.nsfw),
+        };
+
+        // У манги в API нет ни сезона, ни диапазонов, все числовые фильтры применяем сами
+        let client = &client;
+        let make_params = &make_params;
+        let search_text = search.as_str();
+        let fetch = |api_page: u32, api_limit: u32| async move {
+            let mangas = requests::send_with(priority, client.mangas(make_params(api_page, api_limit))).await?;
+            Ok(mangas
+                .into_iter()
+                .map(|m| {
+                    let keep = score.contains(m.score)
+                        && years.contains(m.aired_on.as_ref().and_then(|d| d.year).map(|y| y as u16))
+                        && volumes.contains(m.volumes.filter(|v| *v > 0))
+                        && chapters.contains(m.chapters.filter(|c| *c > 0));
+                    if !keep {
+                        return None;
+                    }
+                    let names = [&m.english, &m.japanese, &m.license_name_ru, &m.russian]
+                        .into_iter()
+                        .flatten()
+                        .chain(m.synonyms.iter().flatten())
+                        .map(String::as_str);
+                    let similarity = fuzzy::score(search_text, std::iter::once(m.name.as_str()).chain(names));
+                    Some((similarity, Manga {
+                        id: m.id,
+                        titles: Names {
+                            title: &m.name,
+                            russian: m.russian.as_deref(),
+                            license_name_ru: m.license_name_ru.as_deref(),
+                            english: m.english.as_deref(),
+                            japanese: m.japanese.as_deref(),
+                        }
+                        .titles(),
+                        title: m.name,
+                        russian: m.russian,
+                        url: m.url.or_else(|| Some(format!("https://shikimori.one/mangas/{}", m.id))),
+                        poster_url: m.poster.and_then(|p| p.main_url),
+                        score: m.score,
+                        kind: m.kind,
+                        status: m.status,
+                        volumes: m.volumes,
+                        chapters: m.chapters,
+                    }))
+                })
+                .collect::<Vec<_>>())
+        };
+
+        let (ranked, has_next_page): (Vec<(f64, Manga)>, bool) = if post_filtered {
+            let key = format!(
+                "manga:{}:{:?}:{}:{:?}:{:?}:{:?}:{:?}:{:?}:{:?}:{:?}:{:?}:{:?}:{}",
+                search, ids, limit, kind, status, genre, publisher, order,
+                score, years, volumes, chapters, settings.nsfw
+            );
+            refill::filtered_page(&key, page, limit, |api_page| fetch(api_page, limit)).await?
+        } else {
+            let window = paging::window(page, limit);
+            let items = fetch(window.api_page, window.api_limit).await?;
+            let (items, has_next_page) = window.split(items, limit);
+            (items.into_iter().flatten().collect(), has_next_page)
+        };
+
+        if ranked.is_empty() && page == 1 && ids.is_none() && corrected_query.is_none() {
+            if let Some(switched) = fuzzy::switch_layout(&search) {
+                corrected_query = Some(switched.clone());
+                search = switched;
+                continue;
+            }
+        }
+
+        let items = if order.is_none() && !search.is_empty() {
+            fuzzy::rerank(ranked)
+        } else {
+            ranked.into_iter().map(|(_, manga)| manga).collect()
+        };
+        break SearchResult::new(items, page, limit, has_next_page);
+    };
+
+    Ok(SearchResult { corrected_query, ..manga_list })
+}
+
+#[tauri::command]
+async fn search_characters(
+    app_handle: tauri::AppHandle,
+    query: String,
     page: Option<u32>,
     limit: Option<u32>,
     ids: Option<Vec<String>>,
+    is_anime: Option<bool>,
+    is_manga: Option<bool>,
+    is_ranobe: Option<bool>,
+    anime_id: Option<i64>,
+    role: Option<String>,
+    request_token: Option<String>,
 ) -> Result<SearchResult<Character>, ApiError> {
-    let client = ShikicrateClient::new().map_err(ApiError::from)?;
+    requests::run(request_token, async move {
+        use filters::{CharacterRoleKind, FilterList, FilterToken};
+
+        let roles: FilterList<CharacterRoleKind> = role
+            .as_deref()
+            .unwrap_or_default()
+            .parse()
+            .map_err(|e| ApiError::validation(tf("validation.parameter", &[("param", &"role"), ("reason", &e)])))?;
+        if !roles.0.is_empty() && anime_id.is_none() {
+            return Err(ApiError::validation(t("validation.role_requires_anime").to_string()));
+        }
+        // Карточка аниме не говорит, встречается ли персонаж в манге или ранобэ
+        if anime_id.is_some() && (is_manga.is_some() || is_ranobe.is_some() || is_anime == Some(false)) {
+            return Err(ApiError::validation(t("validation.media_with_anime").to_string()));
+        }
+
+        let spoiler_mode = get_settings(app_handle).spoiler_mode;
+        let client = ShikicrateClient::new().map_err(ApiError::from)?;
     
-    use shikicrate::queries::CharacterSearchParams;
+        use shikicrate::queries::CharacterSearchParams;
     
-    if let Some(ids) = ids {
-        let params = CharacterSearchParams {
-            search: None,
-            page: None,
-            limit: None,
-            ids: Some(ids),
-        };
+        if let Some(ids) = ids {
+            let params = CharacterSearchParams {
+                search: None,
+                page: None,
+                limit: Some(ids.len().clamp(1, paging::API_MAX_LIMIT as usize) as i32),
+                ids: Some(ids),
+            };
         
-        let characters = client.characters(params).await.map_err(ApiError::from)?;
+            let characters = requests::send(client.characters(params)).await?;
         
-        let character_list: Vec<Character> = characters
-            .into_iter()
-            .map(|c| Character {
-                id: c.id,
-                name: c.name,
-                russian: c.russian,
-                url: c.url.or_else(|| Some(format!("https://shikimori.one/characters/{}", c.id))),
-                poster_url: c.poster.and_then(|p| p.main_url),
-                description: c.description,
-                is_anime: c.is_anime,
-                is_manga: c.is_manga,
-                is_ranobe: c.is_ranobe,
-            })
-            .collect();
+            let character_list: Vec<Character> = characters
+                .into_iter()
+                .map(|c| Character {
+                    id: c.id,
+                    titles: Names { title: &c.name, russian: c.russian.as_deref(), ..Default::default() }.titles(),
+                    name: c.name,
+                    russian: c.russian,
+                    url: c.url.or_else(|| Some(format!("https://shikimori.one/characters/{}", c.id))),
+                    poster_url: c.poster.and_then(|p| p.main_url),
+                    description: c.description.map(|d| spoilers::apply_to_bbcode(d, spoiler_mode)),
+                    is_anime: c.is_anime,
+                    is_manga: c.is_manga,
+                    is_ranobe: c.is_ranobe,
+                })
+                .collect();
         
-        let list_len = character_list.len() as u32;
+            let list_len = character_list.len() as u32;
         
-        return Ok(SearchResult {
-            items: character_list,
-            page: 1,
-            limit: list_len,
-        });
-    }
-    
-    let page_val = page.unwrap_or(1);
-    let limit_val = limit.unwrap_or(20);
-    
-    let params = CharacterSearchParams {
-        search: if query.is_empty() { None } else { Some(query) },
-        page: Some(page_val as i32),
-        limit: Some(limit_val as i32),
-        ids: None,
-    };
-    
-    let characters = client.characters(params).await.map_err(ApiError::from)?;
+            return Ok(SearchResult::new(character_list, 1, list_len, false));
+        }
     
-    let character_list: Vec<Character> = characters
-        .into_iter()
-        .map(|c| Character {
-            id: c.id,
-            name: c.name,
-            russian: c.russian,
-            url: c.url.or_else(|| Some(format!("https://shikimori.one/characters/{}", c.id))),
-            poster_url: c.poster.and_then(|p| p.main_url),
-            description: c.description,
-            is_anime: c.is_anime,
-            is_manga: c.is_manga,
-            is_ranobe: c.is_ranobe,
-        })
-        .collect();
+        let page_val = page.unwrap_or(1).max(1);
+        let limit_val = limit.unwrap_or(20);
+        let has_media = |c: &Character| {
+            let matches = |flag: Option<bool>, wanted: Option<bool>| wanted.is_none_or(|w| flag == Some(w));
+            matches(c.is_anime, is_anime) && matches(c.is_manga, is_manga) && matches(c.is_ranobe, is_ranobe)
+        };
+
+        // Персонажи конкретного аниме: берём их из карточки аниме, там же указаны роли
+        if let Some(anime_id) = anime_id {
+            let anime = requests::send(client.anime_detail(anime_id)).await?.ok_or_else(|| ApiError::anime_not_found(anime_id))?;
+
+            let matched: Vec<(f64, Character)> = anime
+                .character_roles
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|r| {
+                    let kinds: Vec<CharacterRoleKind> = r
+                        .roles_en
+                        .iter()
+                        .flatten()
+                        .filter_map(|name| CharacterRoleKind::parse_token(&name.to_lowercase()).ok())
+                        .collect();
+                    roles.matches(&kinds)
+                })
+                .map(|r| Character { is_anime: Some(true), ..convert_character_role(r, spoiler_mode).character })
+                .map(|c| (fuzzy::score(&query, std::iter::once(c.name.as_str()).chain(c.russian.as_deref())), c))
+                .collect();
+            let characters: Vec<Character> = if query.trim().is_empty() {
+                matched.into_iter().map(|(_, c)| c).collect()
+            } else {
+                fuzzy::rank_matches(matched)
+            };
+            let mut characters: Vec<Character> = characters
+                .into_iter()
+                .skip((page_val - 1).saturating_mul(limit_val) as usize)
+                .take(limit_val as usize + 1)
+                .collect();
+            let has_next_page = characters.len() > limit_val as usize;
+            characters.truncate(limit_val as usize);
+            return Ok(SearchResult::new(characters, page_val, limit_val, has_next_page));
+        }
+
+        let make_params = |page: u32, limit: u32| CharacterSearchParams {
+            search: if query.is_empty() { None } else { Some(query.clone()) },
+            page: Some(page as i32),
+            limit: Some(limit as i32),
+            ids: None,
+        };
+
+        // Фильтров по типу произведения в API нет, отбираем сами
+        let client = &client;
+        let make_params = &make_params;
+        let has_media = &has_media;
+        let fetch = |api_page: u32, api_limit: u32| async move {
+            let characters = requests::send(client.characters(make_params(api_page, api_limit))).await?;
+            Ok(characters
+                .into_iter()
+                .map(|c| Character {
+                    id: c.id,
+                    titles: Names { title: &c.name, russian: c.russian.as_deref(), ..Default::default() }.titles(),
+                    name: c.name,
+                    russian: c.russian,
+                    url: c.url.or_else(|| Some(format!("https://shikimori.one/characters/{}", c.id))),
+                    poster_url: c.poster.and_then(|p| p.main_url),
+                    description: c.description.map(|d| spoilers::apply_to_bbcode(d, spoiler_mode)),
+                    is_anime: c.is_anime,
+                    is_manga: c.is_manga,
+                    is_ranobe: c.is_ranobe,
+                })
+                .map(|c| has_media(&c).then_some(c))
+                .collect::<Vec<_>>())
+        };
+
+        let (character_list, has_next_page) = if is_anime.is_some() || is_manga.is_some() || is_ranobe.is_some() {
+            let key = format!("characters:{}:{}:{:?}:{:?}:{:?}", query, limit_val, is_anime, is_manga, is_ranobe);
+            refill::filtered_page(&key, page_val, limit_val, |api_page| fetch(api_page, limit_val)).await?
+        } else {
+            let window = paging::window(page_val, limit_val);
+            let (items, has_next_page) = window.split(fetch(window.api_page, window.api_limit).await?, limit_val);
+            (items.into_iter().flatten().collect(), has_next_page)
+        };
     
-    Ok(SearchResult {
-        items: character_list,
-        page: page_val,
-        limit: limit_val,
+        Ok(SearchResult::new(character_list, page_val, limit_val, has_next_page))
     })
+    .await
 }
 
 #[tauri::command]
-async fn get_character_details(id: i64) -> Result<CharacterDetail, ApiError> {
-    println!("--- [Backend] Вызов get_character_details REST (ID: {}) ---", id);
-    let client = ShikicrateClient::new().map_err(ApiError::from)?;
-    
-    // Используем REST API для получения полной информации (сейю, аниме, манга) в одном запросе
-    let character = client.get_rest::<RestCharacter, ()>(&format!("characters/{}", id), None)
-        .await
-        .map_err(ApiError::from)?;
-        
+async fn get_character_details(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    collapse_spoilers: Option<bool>,
+    request_token: Option<String>,
+) -> Result<CharacterDetail, ApiError> {
+    let key = response_cache::detail_key("character", id, collapse_spoilers);
+    requests::run_shared(request_token, key, async move {
+        println!("--- [Backend] Вызов get_character_details (ID: {}) ---", id);
+        let client = ShikicrateClient::new().map_err(ApiError::from)?;
+
+        use shikicrate::queries::CharacterSearchParams;
+
+        // GraphQL отдаёт имена, постеры всех размеров и описание, но не роли и сейю:
+        // их берём из REST, который заодно подстрахует, если GraphQL не ответил
+        let graphql_params = CharacterSearchParams {
+            search: None,
+            page: None,
+            limit: Some(1),
+            ids: Some(vec![id.to_string()]),
+        };
+        let (graphql, rest) = tokio::join!(
+            requests::send(client.characters(graphql_params)),
+            requests::send(client.get_rest::<RestCharacter, ()>(&format!("characters/{}", id), None)),
+        );
+        let graphql = match graphql {
+            Ok(characters) => characters.into_iter().next().map(|c| GraphqlCharacter {
+                name: c.name,
+                russian: c.russian,
+                url: c.url,
+                poster: c.poster.map(convert_poster),
+                description: c.description,
+            }),
+            Err(e) => {
+                println!("[Backend] GraphQL не вернул персонажа {}: {}", id, e);
+                None
+            }
+        };
+        let (rest, rest_error) = match rest {
+            Ok(character) => (Some(character), None),
+            Err(e) => (None, Some(e)),
+        };
+
+        let source = match (&graphql, &rest) {
+            (Some(_), Some(_)) => DetailSource::Merged,
+            (Some(_), None) => DetailSource::Graphql,
+            (None, Some(_)) => DetailSource::Rest,
+            (None, None) => {
+                return Err(match rest_error {
+                    // 404 от REST значит то же, что пустой ответ GraphQL
+                    Some(e) if e.status != Some(404) => e,
+                    _ => ApiError::character_not_found(id),
+                });
+            }
+        };
+        println!("[Backend] Персонаж {}: источник {:?}", id, source);
+
+        let spoiler_mode = get_settings(app_handle).spoiler_mode;
+        let sanitize_options = SanitizeOptions { collapse_spoilers: collapse_spoilers.unwrap_or(false), spoiler_mode };
+        Ok(merge_character(id, graphql, rest, source, sanitize_options))
+    })
+    .await
+}
+
+/// Скалярные поля персонажа из GraphQL; при слиянии они важнее полей REST.
+struct GraphqlCharacter {
+    name: String,
+    russian: Option<String>,
+    url: Option<String>,
+    poster: Option<Poster>,
+    description: Option<String>,
+}
+
+/// Собирает карточку персонажа: имена, постер и описание из GraphQL, если они
+/// есть, остальное (роли, сейю, HTML описания, японское имя) из REST.
+fn merge_character(
+    id: i64,
+    graphql: Option<GraphqlCharacter>,
+    rest: Option<RestCharacter>,
+    source: DetailSource,
+    sanitize_options: SanitizeOptions,
+) -> CharacterDetail {
+    let spoiler_mode = sanitize_options.spoiler_mode;
+    let (graphql_name, graphql_russian, graphql_url, graphql_poster, graphql_description) = match graphql {
+        Some(c) => (Some(c.name), c.russian, c.url, c.poster, c.description),
+        None => (None, None, None, None, None),
+    };
+    let RestCharacter { name, russian, japanese, altname, image, url, description, description_html, seyu, animes, mangas, .. } =
+        rest.unwrap_or_else(RestCharacter::empty);
+
+    let poster = graphql_poster.or_else(|| image.map(Poster::from));
+    let (description, description_rich) = convert_description(graphql_description.or(description), spoiler_mode);
     let mut roles = Vec::new();
-    
+
     // Мапим аниме роли
-    for a in character.animes {
+    for a in animes {
         roles.push(CharacterRoleDetail {
             id: a.id,
-            roles_ru: a.roles,
+            roles_ru: spoilers::apply_to_labels(a.roles, spoiler_mode),
             anime: Some(Anime {
                 id: a.id,
-                title: a.name.unwrap_or_else(|| "Unknown".to_string()),
+                titles: Names { title: a.name.as_deref().unwrap_or_default(), russian: a.russian.as_deref(), ..Default::default() }.titles(),
+                title: a.name.or_else(|| a.russian.clone()).unwrap_or_else(|| t("common.unknown").to_string()),
                 russian: a.russian,
                 url: fix_url(a.url),
-                poster_url: a.image.and_then(|img| fix_url(img.original)),
+                poster_url: rest_poster_url(a.image, PosterSize::Preview),
                 score: a.score,
                 kind: a.kind,
                 status: a.status,
@@ -818,19 +1639,20 @@ async fn get_character_details(id: i64) -> Result<CharacterDetail, ApiError> {
             manga: None,
         });
     }
-    
+
     // Мапим манга роли
-    for m in character.mangas {
+    for m in mangas {
         roles.push(CharacterRoleDetail {
             id: m.id,
-            roles_ru: m.roles,
+            roles_ru: spoilers::apply_to_labels(m.roles, spoiler_mode),
             anime: None,
             manga: Some(Manga {
                 id: m.id,
-                title: m.name.unwrap_or_else(|| "Unknown".to_string()),
+                titles: Names { title: m.name.as_deref().unwrap_or_default(), russian: m.russian.as_deref(), ..Default::default() }.titles(),
+                title: m.name.or_else(|| m.russian.clone()).unwrap_or_else(|| t("common.unknown").to_string()),
                 russian: m.russian,
                 url: fix_url(m.url),
-                poster_url: m.image.and_then(|img| fix_url(img.original)),
+                poster_url: rest_poster_url(m.image, PosterSize::Preview),
                 score: m.score,
                 kind: m.kind,
                 status: m.status,
@@ -840,73 +1662,370 @@ async fn get_character_details(id: i64) -> Result<CharacterDetail, ApiError> {
         });
     }
 
-    Ok(CharacterDetail {
-        id: character.id,
-        name: character.name.unwrap_or_else(|| "Unknown".to_string()),
-        russian: character.russian,
-        japanese: character.japanese,
-        synonyms: character.altname
+    let russian = graphql_russian.or(russian);
+    let name = graphql_name.or(name).or_else(|| russian.clone()).unwrap_or_else(|| t("common.unknown").to_string());
+    CharacterDetail {
+        id,
+        titles: Names { title: &name, russian: russian.as_deref(), japanese: japanese.as_deref(), ..Default::default() }.titles(),
+        name,
+        russian,
+        japanese,
+        synonyms: altname
             .map(|s| s.split(", ").map(|item| item.to_string()).collect())
             .unwrap_or_default(),
-        url: fix_url(character.url),
-        poster_url: character.image.and_then(|img| fix_url(img.original)),
-        description: character.description,
-        description_html: character.description_html,
+        url: fix_url(graphql_url.or(url)).or_else(|| Some(format!("https://shikimori.one/characters/{}", id))),
+        poster_url: poster.as_ref().and_then(|p| p.url(PosterSize::Main)),
+        poster,
+        description,
+        description_rich,
+        description_html: description_html.map(|h| html::sanitize(&h, sanitize_options)),
         character_roles: roles,
-        seyus: character.seyu.into_iter().map(|s| {
+        seyus: seyu.into_iter().map(|s| {
             Person {
                 id: s.id,
-                name: s.name.unwrap_or_else(|| "Unknown".to_string()),
+                titles: Names { title: s.name.as_deref().unwrap_or_default(), russian: s.russian.as_deref(), ..Default::default() }.titles(),
+                name: s.name.or_else(|| s.russian.clone()).unwrap_or_else(|| t("common.unknown").to_string()),
                 russian: s.russian,
                 url: fix_url(s.url),
-                poster_url: s.image.and_then(|img| fix_url(img.original)),
+                poster_url: rest_poster_url(s.image, PosterSize::Preview),
                 is_seyu: Some(true),
                 is_mangaka: None,
                 is_producer: None,
                 website: None,
             }
         }).collect(),
+        source,
+    }
+}
+
+/// Страница из найденных людей, уже отобранных по ролям. `received` — сколько
+/// людей вернул API: если он упёрся в `API_MAX_LIMIT`, общее число неизвестно.
+fn people_page<T>(people: Vec<T>, received: usize, page: u32, limit: u32) -> SearchResult<T> {
+    let mut people: Vec<_> = people.into_iter().skip((page - 1).saturating_mul(limit) as usize).take(limit as usize + 1).collect();
+    let has_next_page = people.len() > limit as usize;
+    people.truncate(limit as usize);
+    let mut result = SearchResult::new(people, page, limit, has_next_page);
+    if received >= paging::API_MAX_LIMIT as usize {
+        result.total = None;
+    }
+    result
+}
+
+/// Поиск людей с фильтрами по ролям. API отдаёт не больше первых 50 человек
+/// (`API_MAX_LIMIT`) и не листается, поэтому страницы за этой границей
+/// отклоняются, а если API вернул все 50, `total` остаётся неизвестным.
+#[tauri::command]
+async fn search_people(
+    query: String,
+    page: Option<u32>,
+    limit: Option<u32>,
+    is_seyu: Option<bool>,
+    is_mangaka: Option<bool>,
+    is_producer: Option<bool>,
+    request_token: Option<String>,
+) -> Result<SearchResult<Person>, ApiError> {
+    requests::run(request_token, async move {
+        let page = page.unwrap_or(1).max(1);
+        let limit = limit.unwrap_or(20);
+        if limit == 0 || limit > paging::API_MAX_LIMIT {
+            return Err(ApiError::validation(tf("validation.limit_range", &[("max", &paging::API_MAX_LIMIT)])));
+        }
+        if (page - 1).saturating_mul(limit) >= paging::API_MAX_LIMIT {
+            return Err(ApiError::validation(tf("validation.people_page_cap", &[("max", &paging::API_MAX_LIMIT), ("page", &page)])));
+        }
+
+        let client = ShikicrateClient::new().map_err(ApiError::from)?;
+    
+        use shikicrate::queries::PeopleSearchParams;
+
+        // Ни страниц, ни фильтров по ролям у поиска людей в API нет: запрашиваем сколько
+        // нужно до конца страницы плюс один элемент (с фильтрами по ролям сразу максимум),
+        // отбираем по ролям и вырезаем страницу сами. Дальше первых 50 результатов API не листается.
+        let filtered = is_seyu.is_some() || is_mangaka.is_some() || is_producer.is_some();
+        let wanted = if filtered { paging::API_MAX_LIMIT } else { page.saturating_mul(limit).saturating_add(1).min(paging::API_MAX_LIMIT) };
+        let params = PeopleSearchParams {
+            search: if query.is_empty() { None } else { Some(query) },
+            limit: Some(wanted as i32),
+        };
+    
+        let people = requests::send(client.people(params)).await?;
+        let received = people.len();
+        let has_role = |flag: Option<bool>, wanted: Option<bool>| wanted.is_none_or(|w| flag.unwrap_or(false) == w);
+        let person_list: Vec<Person> = people
+            .into_iter()
+            .filter(|p| has_role(p.is_seyu, is_seyu) && has_role(p.is_mangaka, is_mangaka) && has_role(p.is_producer, is_producer))
+            .map(|p| Person {
+                id: p.id,
+                titles: Names { title: &p.name, russian: p.russian.as_deref(), ..Default::default() }.titles(),
+                name: p.name,
+                russian: p.russian,
+                url: p.url.or_else(|| Some(format!("https://shikimori.one/people/{}", p.id))),
+                poster_url: p.poster.and_then(|p| p.main_url),
+                is_seyu: p.is_seyu,
+                is_mangaka: p.is_mangaka,
+                is_producer: p.is_producer,
+                website: p.website,
+            })
+            .collect();
+    
+        Ok(people_page(person_list, received, page, limit))
     })
+    .await
+}
+
+fn join_ids(ids: &[i64]) -> String {
+    ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",")
+}
+
+#[tauri::command]
+async fn get_animes_by_ids(app_handle: tauri::AppHandle, ids: Vec<i64>) -> Result<Vec<ByIdEntry<Anime>>, ApiError> {
+    let fetch = |chunk: Vec<i64>| {
+        let app_handle = app_handle.clone();
+        async move {
+            let search = AnimeSearch {
+                ids: Some(join_ids(&chunk)),
+                page: Some(1),
+                limit: Some(paging::API_MAX_LIMIT),
+                ..Default::default()
+            };
+            let result = run_anime_search(app_handle, search, Priority::Foreground).await?;
+            Ok(result.items)
+        }
+    };
+    batch::lookup(ids, fetch, |a: &Anime| a.id).await
+}
+
+#[tauri::command]
+async fn get_mangas_by_ids(app_handle: tauri::AppHandle, ids: Vec<i64>) -> Result<Vec<ByIdEntry<Manga>>, ApiError> {
+    let fetch = |chunk: Vec<i64>| {
+        let app_handle = app_handle.clone();
+        async move {
+            let search = MangaSearch {
+                ids: Some(join_ids(&chunk)),
+                page: Some(1),
+                limit: Some(paging::API_MAX_LIMIT),
+                ..Default::default()
+            };
+            let result = run_manga_search(app_handle, search, Priority::Foreground).await?;
+            Ok(result.items)
+        }
+    };
+    batch::lookup(ids, fetch, |m: &Manga| m.id).await
+}
+
+#[tauri::command]
+async fn get_characters_by_ids(app_handle: tauri::AppHandle, ids: Vec<i64>) -> Result<Vec<ByIdEntry<Character>>, ApiError> {
+    let fetch = |chunk: Vec<i64>| {
+        let app_handle = app_handle.clone();
+        async move {
+            let ids = chunk.iter().map(|id| id.to_string()).collect();
+            let result = search_characters(
+                app_handle, String::new(), None, None, Some(ids), None, None, None, None, None, None,
+            ).await?;
+            Ok(result.items)
+        }
+    };
+    batch::lookup(ids, fetch, |c: &Character| c.id).await
+}
+
+#[tauri::command]
+async fn search_all(
+    app_handle: tauri::AppHandle,
+    query: String,
+    limit: Option<u32>,
+    on_event: tauri::ipc::Channel<SearchAllEvent>,
+) -> Result<Vec<SearchAllHit>, ApiError> {
+    println!(">>> [Backend] search_all вызвана: query='{}', limit={:?}", query, limit);
+
+    let limit = Some(limit.unwrap_or(10));
+    let spawn = |entity: SearchEntity, search: search_all::SearchFuture| {
+        tauri::async_runtime::spawn(search_all::run(entity, query.clone(), on_event.clone(), search))
+    };
+
+    let tasks = vec![
+        spawn(SearchEntity::Anime, {
+            let (app_handle, query) = (app_handle.clone(), query.clone());
+            Box::pin(async move {
+                let search = AnimeSearch { query, page: Some(1), limit, ..Default::default() };
+                let result = run_anime_search(app_handle, search, Priority::Foreground).await?;
+                Ok(result.items.into_iter().map(SearchAllItem::Anime).collect())
+            })
+        }),
+        spawn(SearchEntity::Manga, {
+            let (app_handle, query) = (app_handle.clone(), query.clone());
+            Box::pin(async move {
+                let search = MangaSearch { query, page: Some(1), limit, ..Default::default() };
+                let result = run_manga_search(app_handle, search, Priority::Foreground).await?;
+                Ok(result.items.into_iter().map(SearchAllItem::Manga).collect())
+            })
+        }),
+        spawn(SearchEntity::Character, {
+            let (app_handle, query) = (app_handle.clone(), query.clone());
+            Box::pin(async move {
+                let result = search_characters(app_handle, query, Some(1), limit, None, None, None, None, None, None, None).await?;
+                Ok(result.items.into_iter().map(SearchAllItem::Character).collect())
+            })
+        }),
+        spawn(SearchEntity::Person, {
+            let query = query.clone();
+            Box::pin(async move {
+                let result = search_people(query, Some(1), limit, None, None, None, None).await?;
+                Ok(result.items.into_iter().map(SearchAllItem::Person).collect())
+            })
+        }),
+        spawn(SearchEntity::Studio, {
+            let query = query.clone();
+            Box::pin(async move {
+                let studios = search_studios(query, None).await?;
+                Ok(studios.into_iter().map(SearchAllItem::Studio).collect())
+            })
+        }),
+        spawn(SearchEntity::Publisher, {
+            let query = query.clone();
+            Box::pin(async move {
+                let publishers = search_publishers(query, None).await?;
+                Ok(publishers.into_iter().map(SearchAllItem::Publisher).collect())
+            })
+        }),
+    ];
+
+    let mut results = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        results.push(task.await.unwrap_or_else(|e| {
+            Err(ApiError::task_failed(e))
+        }));
+    }
+    search_all::merge(results)
+}
+
+fn get_history_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(get_config_path(app_handle)?.with_file_name(history::HISTORY_FILE))
+}
+
+#[tauri::command]
+fn record_search(
+    app_handle: tauri::AppHandle,
+    query: String,
+    content_type: ContentType,
+    filters: Option<std::collections::BTreeMap<String, String>>,
+    clicked: Option<ClickedResult>,
+) -> Result<(), ApiError> {
+    if !get_settings(app_handle.clone()).search_history_enabled {
+        return Ok(());
+    }
+    let path = get_history_path(&app_handle).map_err(ApiError::storage)?;
+    history::record(path, &query, content_type, filters.unwrap_or_default(), clicked).map_err(ApiError::storage)
+}
+
+#[tauri::command]
+fn get_search_history(
+    app_handle: tauri::AppHandle,
+    content_type: Option<ContentType>,
+    limit: Option<usize>,
+) -> Result<Vec<HistoryEntry>, ApiError> {
+    if !get_settings(app_handle.clone()).search_history_enabled {
+        return Ok(Vec::new());
+    }
+    let path = get_history_path(&app_handle).map_err(ApiError::storage)?;
+    Ok(history::list(path, content_type, limit.unwrap_or(10)))
+}
+
+#[tauri::command]
+fn delete_search_history_entry(app_handle: tauri::AppHandle, id: u64) -> Result<(), ApiError> {
+    history::remove(get_history_path(&app_handle).map_err(ApiError::storage)?, id).map_err(ApiError::storage)
+}
+
+#[tauri::command]
+fn clear_search_history(app_handle: tauri::AppHandle) -> Result<(), ApiError> {
+    history::clear(get_history_path(&app_handle).map_err(ApiError::storage)?).map_err(ApiError::storage)
+}
+
+#[tauri::command]
+async fn get_search_suggestions(
+    app_handle: tauri::AppHandle,
+    query: String,
+    content_type: Option<ContentType>,
+    limit: Option<usize>,
+) -> Result<Vec<SearchSuggestion>, ApiError> {
+    let limit = limit.unwrap_or(10);
+    let settings = get_settings(app_handle.clone());
+
+    let past = if settings.search_history_enabled {
+        let path = get_history_path(&app_handle).map_err(ApiError::storage)?;
+        history::suggestions(path, &query, content_type, limit)
+    } else {
+        Vec::new()
+    };
+
+    let mut suggestions: Vec<SearchSuggestion> = past
+        .iter()
+        .map(|e| SearchSuggestion::History { id: e.id, query: e.query.clone(), content_type: e.content_type })
+        .collect();
+
+    // Автодополнение по названиям есть только у аниме; повторяющие прошлые запросы тайтлы пропускаем
+    let wants_anime = content_type.is_none_or(|t| t == ContentType::Anime);
+    if wants_anime && query.trim().chars().count() >= 2 && suggestions.len() < limit {
+        let remaining = (limit - suggestions.len()) as u32;
+        let animes = search_anime_lite(app_handle, query, None, None, None, None, Some(remaining), None).await?;
+        let known = |name: &str| past.iter().any(|e| e.query.to_lowercase() == name.to_lowercase());
+        suggestions.extend(
+            animes
+                .into_iter()
+                .filter(|a| !known(&a.title) && !a.russian.as_deref().is_some_and(known))
+                .map(|a| SearchSuggestion::Anime { id: a.id, title: a.title, russian: a.russian }),
+        );
+    }
+
+    suggestions.truncate(limit);
+    Ok(suggestions)
+}
+
+fn get_saved_searches_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(get_config_path(app_handle)?.with_file_name(saved_searches::SAVED_SEARCHES_FILE))
+}
+
+#[tauri::command]
+async fn create_saved_search(
+    app_handle: tauri::AppHandle,
+    name: String,
+    params: SavedSearchParams,
+) -> Result<SavedSearch, ApiError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(ApiError::validation(t("validation.saved_search_name").to_string()));
+    }
+    // Заодно проверяет параметры: с неверными фильтрами поиск не сохранится
+    let items = saved_searches::execute(&app_handle, &params).await?;
+    let path = get_saved_searches_path(&app_handle).map_err(ApiError::storage)?;
+    let known_ids = items.into_iter().map(|i| i.id).collect();
+    saved_searches::create(path, name, params, known_ids).map_err(ApiError::storage)
+}
+
+#[tauri::command]
+fn get_saved_searches(app_handle: tauri::AppHandle) -> Result<Vec<SavedSearch>, ApiError> {
+    Ok(saved_searches::list(get_saved_searches_path(&app_handle).map_err(ApiError::storage)?))
+}
+
+#[tauri::command]
+fn delete_saved_search(app_handle: tauri::AppHandle, id: u64) -> Result<(), ApiError> {
+    saved_searches::remove(get_saved_searches_path(&app_handle).map_err(ApiError::storage)?, id).map_err(ApiError::storage)
 }
 
 #[tauri::command]
-async fn search_people(
-    query: String,
-    limit: Option<u32>,
-) -> Result<SearchResult<Person>, ApiError> {
-    let limit = limit.unwrap_or(20);
+async fn check_saved_searches(app_handle: tauri::AppHandle) {
+    saved_searches::check_all(&app_handle).await;
+}
 
-    let client = ShikicrateClient::new().map_err(ApiError::from)?;
-    
-    use shikicrate::queries::PeopleSearchParams;
-    
-    let params = PeopleSearchParams {
-        search: if query.is_empty() { None } else { Some(query) },
-        limit: Some(limit as i32),
-    };
-    
-    let people = client.people(params).await.map_err(ApiError::from)?;
-    
-    let person_list: Vec<Person> = people
-        .into_iter()
-        .map(|p| Person {
-            id: p.id,
-            name: p.name,
-            russian: p.russian,
-            url: p.url.or_else(|| Some(format!("https://shikimori.one/people/{}", p.id))),
-            poster_url: p.poster.and_then(|p| p.main_url),
-            is_seyu: p.is_seyu,
-            is_mangaka: p.is_mangaka,
-            is_producer: p.is_producer,
-            website: p.website,
-        })
-        .collect();
-    
-    Ok(SearchResult {
-        items: person_list,
-        page: 1,
-        limit,
-    })
+/// Ставит детали в очередь фоновой загрузки, чтобы карточка открылась из кэша.
+#[tauri::command]
+fn prefetch(app_handle: tauri::AppHandle, entity: PrefetchEntity, ids: Vec<i64>, priority: Option<PrefetchPriority>) {
+    prefetch::schedule(&app_handle, entity, ids, priority.unwrap_or_default());
+}
+
+/// Отменяет команду, запущенную с этим `request_token`.
+#[tauri::command]
+fn cancel_request(token: String) -> bool {
+    requests::cancel(&token)
 }
 
 fn convert_date(date: Option<shikicrate::types::Date>) -> Option<Date> {
@@ -920,6 +2039,7 @@ fn convert_date(date: Option<shikicrate::types::Date>) -> Option<Date> {
 
 fn convert_genre(genre: shikicrate::types::Genre) -> Genre {
     Genre {
+        label: genre.russian.clone().unwrap_or_else(|| genre.name.clone()),
         id: genre.id,
         name: genre.name,
         russian: genre.russian,
@@ -952,13 +2072,14 @@ fn convert_external_link(link: shikicrate::types::ExternalLink) -> ExternalLink
     }
 }
 
-fn convert_person_role(role: shikicrate::types::PersonRole) -> PersonRole {
+fn convert_person_role(role: shikicrate::types::PersonRole, spoiler_mode: SpoilerMode) -> PersonRole {
     PersonRole {
         id: role.id,
-        roles_ru: role.roles_ru,
-        roles_en: role.roles_en,
+        roles_ru: role.roles_ru.map(|r| spoilers::apply_to_labels(r, spoiler_mode)),
+        roles_en: role.roles_en.map(|r| spoilers::apply_to_labels(r, spoiler_mode)),
         person: Person {
             id: role.person.id,
+            titles: Names { title: &role.person.name, russian: role.person.russian.as_deref(), ..Default::default() }.titles(),
             name: role.person.name,
             russian: role.person.russian,
             url: Some(format!("https://shikimori.one/people/{}", role.person.id)),
@@ -971,20 +2092,21 @@ fn convert_person_role(role: shikicrate::types::PersonRole) -> PersonRole {
     }
 }
 
-fn convert_character_role(role: shikicrate::types::CharacterRole) -> CharacterRole {
+fn convert_character_role(role: shikicrate::types::CharacterRole, spoiler_mode: SpoilerMode) -> CharacterRole {
     let char_data = role.character.unwrap_or(shikicrate::types::Character {
         id: 0,
-        name: "Unknown".to_string(),
+        name: t("common.unknown").to_string(),
         russian: None,
         poster: None,
     });
     
     CharacterRole {
         id: role.id,
-        roles_ru: role.roles_ru,
-        roles_en: role.roles_en,
+        roles_ru: role.roles_ru.map(|r| spoilers::apply_to_labels(r, spoiler_mode)),
+        roles_en: role.roles_en.map(|r| spoilers::apply_to_labels(r, spoiler_mode)),
         character: Character {
             id: char_data.id,
+            titles: Names { title: &char_data.name, russian: char_data.russian.as_deref(), ..Default::default() }.titles(),
             name: char_data.name,
             russian: char_data.russian,
             url: Some(format!("https://shikimori.one/characters/{}", char_data.id)),
@@ -997,6 +2119,19 @@ fn convert_character_role(role: shikicrate::types::CharacterRole) -> CharacterRo
     }
 }
 
+fn validate_score(score: filters::NumericRange<f64>) -> Result<(), ApiError> {
+    if [score.min, score.max].into_iter().flatten().any(|s| !(0.0..=10.0).contains(&s)) {
+        return Err(ApiError::validation(t("validation.score_range").to_string()));
+    }
+    score.validate("score").map_err(ApiError::validation)
+}
+
+/// Описание и его разобранное дерево с учётом режима спойлеров.
+fn convert_description(description: Option<String>, spoiler_mode: SpoilerMode) -> (Option<String>, Option<Vec<RichNode>>) {
+    let rich = description.as_deref().map(|d| spoilers::apply_to_rich(bbcode::parse(d), spoiler_mode));
+    (description.map(|d| spoilers::apply_to_bbcode(d, spoiler_mode)), rich)
+}
+
 fn fix_url(url: Option<String>) -> Option<String> {
     url.map(|u| {
         if u.starts_with('/') {
@@ -1020,9 +2155,16 @@ fn convert_poster(poster: shikicrate::types::Poster) -> Poster {
     }
 }
 
-fn convert_related(related: shikicrate::types::Related) -> Related {
+fn convert_related(related: shikicrate::types::Related, spoiler_mode: SpoilerMode) -> Related {
+    let (name, russian) = match (&related.anime, &related.manga) {
+        (Some(a), _) => (a.name.as_deref(), a.russian.as_deref()),
+        (None, Some(m)) => (m.name.as_deref(), m.russian.as_deref()),
+        (None, None) => (None, None),
+    };
+    let titles = Names { title: name.unwrap_or_default(), russian, ..Default::default() }.titles();
     Related {
         id: related.id,
+        titles,
         anime: related.anime.map(|a| RelatedAnime {
             id: a.id,
             name: a.name,
@@ -1036,7 +2178,7 @@ fn convert_related(related: shikicrate::types::Related) -> Related {
             image: m.poster.map(convert_poster),
         }),
         relation_kind: related.relation_kind,
-        relation_text: related.relation_text,
+        relation_text: related.relation_text.and_then(|text| spoilers::apply_to_label(text, spoiler_mode)),
     }
 }
 
@@ -1074,8 +2216,274 @@ fn convert_status_stat(stat: shikicrate::types::StatusStat) -> StatusStat {
     }
 }
 
-#[tauri::command]
-async fn get_anime_by_id(id: i64) -> Result<AnimeDetail, ApiError> {
+/// Дата REST вида `2002-10-03`.
+fn rest_date(date: Option<String>) -> Option<Date> {
+    let date = date?;
+    let mut parts = date.split('-').map(|p| p.parse::<i32>().ok());
+    Some(Date {
+        year: parts.next().flatten(),
+        month: parts.next().flatten(),
+        day: parts.next().flatten(),
+        date: Some(date),
+    })
+}
+
+/// REST отдаёт статусы списков подписями, GraphQL — ключами; приводим к ключам.
+fn rest_status_stat(stat: RestStatusStat) -> StatusStat {
+    let status = match stat.name.as_str() {
+        "Запланировано" => "planned",
+        "Смотрю" | "Читаю" => "watching",
+        "Просмотрено" | "Прочитано" => "completed",
+        "Отложено" => "on_hold",
+        "Брошено" => "dropped",
+        other => other,
+    };
+    StatusStat { status: status.to_string(), count: stat.value }
+}
+
+fn rest_score_stat(stat: RestScoreStat) -> ScoreStat {
+    ScoreStat { score: stat.name, count: stat.value }
+}
+
+fn rest_genre(genre: RestGenre) -> Genre {
+    Genre {
+        label: genre.russian.clone().unwrap_or_else(|| genre.name.clone()),
+        id: genre.id,
+        name: genre.name,
+        russian: genre.russian,
+        kind: genre.kind,
+    }
+}
+
+fn rest_video(video: RestVideo) -> Video {
+    Video {
+        id: video.id,
+        url: video.url,
+        name: video.name,
+        kind: video.kind,
+        player_url: video.player_url,
+        image_url: video.image_url,
+    }
+}
+
+fn first_title(titles: Vec<Option<String>>) -> Option<String> {
+    titles.into_iter().flatten().next()
+}
+
+/// Шапка карточки аниме из REST: без ролей, связанных тайтлов, скриншотов и ссылок.
+fn convert_rest_anime(anime: RestAnimeDetail, sanitize_options: SanitizeOptions) -> AnimeDetail {
+    let spoiler_mode = sanitize_options.spoiler_mode;
+    let english = first_title(anime.english);
+    let japanese = first_title(anime.japanese);
+    let (description, description_rich) = convert_description(anime.description, spoiler_mode);
+    AnimeDetail {
+        id: anime.id,
+        mal_id: anime.myanimelist_id,
+        titles: Names {
+            title: &anime.name,
+            russian: anime.russian.as_deref(),
+            license_name_ru: anime.license_name_ru.as_deref(),
+            english: english.as_deref(),
+            japanese: japanese.as_deref(),
+        }
+        .titles(),
+        title: anime.name,
+        russian: anime.russian,
+        license_name_ru: anime.license_name_ru,
+        english,
+        japanese,
+        synonyms: anime.synonyms,
+        url: fix_url(anime.url).or_else(|| Some(format!("https://shikimori.one/animes/{}", anime.id))),
+        poster_url: rest_poster_url(anime.image, PosterSize::Main),
+        description,
+        description_rich,
+        description_html: anime.description_html.map(|h| html::sanitize(&h, sanitize_options)),
+        description_source: anime.description_source,
+        score: anime.score,
+        kind: anime.kind,
+        rating: anime.rating,
+        status: anime.status,
+        episodes: anime.episodes,
+        episodes_aired: anime.episodes_aired,
+        duration: anime.duration,
+        aired_on: rest_date(anime.aired_on),
+        released_on: rest_date(anime.released_on),
+        season: None,
+        next_episode_at: anime.next_episode_at,
+        is_censored: None,
+        genres: Some(anime.genres.into_iter().map(rest_genre).collect()),
+        studios: Some(
+            anime
+                .studios
+                .into_iter()
+                .map(|s| Studio { id: s.id, name: s.name, image_url: fix_url(s.image) })
+                .collect(),
+        ),
+        external_links: None,
+        person_roles: None,
+        character_roles: None,
+        related: None,
+        videos: Some(anime.videos.into_iter().map(rest_video).collect()),
+        screenshots: None,
+        scores_stats: Some(anime.rates_scores_stats.into_iter().map(rest_score_stat).collect()),
+        statuses_stats: Some(anime.rates_statuses_stats.into_iter().map(rest_status_stat).collect()),
+        fansubbers: anime.fansubbers,
+        fandubbers: anime.fandubbers,
+        licensors: anime.licensors,
+    }
+}
+
+/// Шапка карточки манги из REST, см. [`convert_rest_anime`].
+fn convert_rest_manga(manga: RestMangaDetail, sanitize_options: SanitizeOptions) -> MangaDetail {
+    let spoiler_mode = sanitize_options.spoiler_mode;
+    let english = first_title(manga.english);
+    let japanese = first_title(manga.japanese);
+    let (description, description_rich) = convert_description(manga.description, spoiler_mode);
+    MangaDetail {
+        id: manga.id,
+        mal_id: manga.myanimelist_id,
+        titles: Names {
+            title: &manga.name,
+            russian: manga.russian.as_deref(),
+            license_name_ru: manga.license_name_ru.as_deref(),
+            english: english.as_deref(),
+            japanese: japanese.as_deref(),
+        }
+        .titles(),
+        title: manga.name,
+        russian: manga.russian,
+        license_name_ru: manga.license_name_ru,
+        english,
+        japanese,
+        synonyms: manga.synonyms,
+        url: fix_url(manga.url).or_else(|| Some(format!("https://shikimori.one/mangas/{}", manga.id))),
+        poster_url: rest_poster_url(manga.image, PosterSize::Main),
+        description,
+        description_rich,
+        description_html: manga.description_html.map(|h| html::sanitize(&h, sanitize_options)),
+        description_source: manga.description_source,
+        score: manga.score,
+        kind: manga.kind,
+        status: manga.status,
+        volumes: manga.volumes,
+        chapters: manga.chapters,
+        aired_on: rest_date(manga.aired_on),
+        released_on: rest_date(manga.released_on),
+        is_censored: None,
+        genres: Some(manga.genres.into_iter().map(rest_genre).collect()),
+        publishers: Some(manga.publishers.into_iter().map(|p| Publisher { id: p.id, name: p.name }).collect()),
+        external_links: None,
+        person_roles: None,
+        character_roles: None,
+        related: None,
+        scores_stats: Some(manga.rates_scores_stats.into_iter().map(rest_score_stat).collect()),
+        statuses_stats: Some(manga.rates_statuses_stats.into_iter().map(rest_status_stat).collect()),
+        licensors: manga.licensors,
+    }
+}
+
+/// Ответ `/roles` содержит и персонажей, и людей вперемешку; делим на два раздела.
+fn convert_rest_roles(roles: Vec<RestRole>, spoiler_mode: SpoilerMode) -> (Vec<CharacterRole>, Vec<PersonRole>) {
+    let mut characters = Vec::new();
+    let mut people = Vec::new();
+    for role in roles {
+        let roles_ru = Some(spoilers::apply_to_labels(role.roles_russian, spoiler_mode));
+        let roles_en = Some(spoilers::apply_to_labels(role.roles, spoiler_mode));
+        if let Some(c) = role.character {
+            characters.push(CharacterRole {
+                id: c.id,
+                roles_ru,
+                roles_en,
+                character: Character {
+                    id: c.id,
+                    titles: Names { title: &c.name, russian: c.russian.as_deref(), ..Default::default() }.titles(),
+                    name: c.name,
+                    russian: c.russian,
+                    url: Some(format!("https://shikimori.one/characters/{}", c.id)),
+                    poster_url: rest_poster_url(c.image, PosterSize::Main),
+                    description: None,
+                    is_anime: None,
+                    is_manga: None,
+                    is_ranobe: None,
+                },
+            });
+        } else if let Some(p) = role.person {
+            people.push(PersonRole {
+                id: p.id,
+                roles_ru,
+                roles_en,
+                person: Person {
+                    id: p.id,
+                    titles: Names { title: &p.name, russian: p.russian.as_deref(), ..Default::default() }.titles(),
+                    name: p.name,
+                    russian: p.russian,
+                    url: Some(format!("https://shikimori.one/people/{}", p.id)),
+                    poster_url: rest_poster_url(p.image, PosterSize::Main),
+                    is_seyu: None,
+                    is_mangaka: None,
+                    is_producer: None,
+                    website: None,
+                },
+            });
+        }
+    }
+    (characters, people)
+}
+
+fn convert_rest_related(related: RestRelated, spoiler_mode: SpoilerMode) -> Related {
+    let entry = related.anime.as_ref().or(related.manga.as_ref());
+    let titles = Names {
+        title: entry.and_then(|e| e.name.as_deref()).unwrap_or_default(),
+        russian: entry.and_then(|e| e.russian.as_deref()),
+        ..Default::default()
+    }
+    .titles();
+    Related {
+        id: entry.map(|e| e.id).unwrap_or_default(),
+        titles,
+        // REST отдаёт «Side story», GraphQL — `side_story`
+        relation_kind: related.relation.to_lowercase().replace([' ', '-'], "_"),
+        relation_text: related.relation_russian.and_then(|text| spoilers::apply_to_label(text, spoiler_mode)),
+        anime: related.anime.map(|a| RelatedAnime {
+            id: Some(a.id),
+            name: a.name,
+            russian: a.russian,
+            image: a.image.map(Poster::from),
+        }),
+        manga: related.manga.map(|m| RelatedManga {
+            id: Some(m.id),
+            name: m.name,
+            russian: m.russian,
+            image: m.image.map(Poster::from),
+        }),
+    }
+}
+
+fn convert_rest_screenshot((index, screenshot): (usize, RestScreenshot)) -> Screenshot {
+    Screenshot {
+        id: index as i64,
+        original_url: fix_url(screenshot.original),
+        x166_url: None,
+        x332_url: fix_url(screenshot.preview),
+    }
+}
+
+fn convert_rest_external_link(link: RestExternalLink) -> ExternalLink {
+    ExternalLink {
+        id: link.id,
+        kind: link.kind,
+        url: link.url,
+        created_at: link.created_at,
+        updated_at: link.updated_at,
+    }
+}
+
+async fn load_anime_detail(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    collapse_spoilers: Option<bool>,
+    priority: Priority,
+) -> Result<AnimeDetail, ApiError> {
     println!("--- [Backend] Вызов get_anime_by_id (ID: {}) ---", id);
     let client = match ShikicrateClient::new() {
         Ok(c) => c,
@@ -1084,29 +2492,36 @@ async fn get_anime_by_id(id: i64) -> Result<AnimeDetail, ApiError> {
             return Err(ApiError::from(e));
         }
     };
-    
+
     // Используем выделенный метод для получения деталей
     println!("[Backend] Поиск аниме по ID через API...");
-    let anime = match client.anime_detail(id).await {
+    let anime = match requests::send_with(priority, client.anime_detail(id)).await {
         Ok(Some(a)) => a,
         Ok(None) => {
             println!("[Backend] Аниме с ID {} не найдено", id);
-            return Err(ApiError {
-                kind: "not_found".to_string(),
-                message: format!("Аниме с ID {} не найдено.", id),
-                retry_after: None,
-            });
+            return Err(ApiError::anime_not_found(id));
         }
         Err(e) => {
-            println!("[Backend] Ошибка API при получении деталей аниме: {:?}", e);
-            return Err(ApiError::from(e));
+            println!("[Backend] Ошибка API при получении деталей аниме: {}", e);
+            return Err(e);
         }
     };
-    
+
     println!("[Backend] Аниме найдено: {}. Преобразование данных...", anime.name);
+    let spoiler_mode = get_settings(app_handle).spoiler_mode;
+    let sanitize_options = SanitizeOptions { collapse_spoilers: collapse_spoilers.unwrap_or(false), spoiler_mode };
+    let (description, description_rich) = convert_description(anime.description, spoiler_mode);
     Ok(AnimeDetail {
         id: anime.id,
         mal_id: anime.mal_id,
+        titles: Names {
+            title: &anime.name,
+            russian: anime.russian.as_deref(),
+            license_name_ru: anime.license_name_ru.as_deref(),
+            english: anime.english.as_deref(),
+            japanese: anime.japanese.as_deref(),
+        }
+        .titles(),
         title: anime.name,
         russian: anime.russian,
         license_name_ru: anime.license_name_ru,
@@ -1115,8 +2530,9 @@ async fn get_anime_by_id(id: i64) -> Result<AnimeDetail, ApiError> {
         synonyms: anime.synonyms,
         url: anime.url.or_else(|| Some(format!("https://shikimori.one/animes/{}", anime.id))),
         poster_url: anime.poster.and_then(|p| p.main_url),
-        description: anime.description,
-        description_html: anime.description_html,
+        description,
+        description_rich,
+        description_html: anime.description_html.map(|h| html::sanitize(&h, sanitize_options)),
         description_source: anime.description_source,
         score: anime.score,
         kind: anime.kind,
@@ -1133,9 +2549,9 @@ async fn get_anime_by_id(id: i64) -> Result<AnimeDetail, ApiError> {
         genres: anime.genres.map(|g| g.into_iter().map(convert_genre).collect()),
         studios: anime.studios.map(|s| s.into_iter().map(convert_studio).collect()),
         external_links: anime.external_links.map(|l| l.into_iter().map(convert_external_link).collect()),
-        person_roles: anime.person_roles.map(|r| r.into_iter().map(convert_person_role).collect()),
-        character_roles: anime.character_roles.map(|r| r.into_iter().map(convert_character_role).collect()),
-        related: anime.related.map(|r| r.into_iter().map(convert_related).collect()),
+        person_roles: anime.person_roles.map(|r| r.into_iter().map(|r| convert_person_role(r, spoiler_mode)).collect()),
+        character_roles: anime.character_roles.map(|r| r.into_iter().map(|r| convert_character_role(r, spoiler_mode)).collect()),
+        related: anime.related.map(|r| r.into_iter().map(|r| convert_related(r, spoiler_mode)).collect()),
         videos: anime.videos.map(|v| v.into_iter().map(convert_video).collect()),
         screenshots: anime.screenshots.map(|s| s.into_iter().map(convert_screenshot).collect()),
         scores_stats: anime.scores_stats.map(|s| s.into_iter().map(convert_score_stat).collect()),
@@ -1147,7 +2563,28 @@ async fn get_anime_by_id(id: i64) -> Result<AnimeDetail, ApiError> {
 }
 
 #[tauri::command]
-async fn get_manga_by_id(id: i64) -> Result<MangaDetail, ApiError> {
+async fn get_anime_by_id(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    collapse_spoilers: Option<bool>,
+    request_token: Option<String>,
+) -> Result<AnimeDetail, ApiError> {
+    let key = response_cache::detail_key("anime", id, collapse_spoilers);
+    if let Some(detail) = response_cache::get(&key) {
+        return Ok(detail);
+    }
+    prefetch::preempt(PrefetchEntity::Anime, id);
+    let detail = requests::run_shared(request_token, key.clone(), load_anime_detail(app_handle, id, collapse_spoilers, Priority::Foreground)).await?;
+    response_cache::insert(key, detail.clone());
+    Ok(detail)
+}
+
+async fn load_manga_detail(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    collapse_spoilers: Option<bool>,
+    priority: Priority,
+) -> Result<MangaDetail, ApiError> {
     println!("--- [Backend] Вызов get_manga_by_id (ID: {}) ---", id);
     let client = match ShikicrateClient::new() {
         Ok(c) => c,
@@ -1156,29 +2593,36 @@ async fn get_manga_by_id(id: i64) -> Result<MangaDetail, ApiError> {
             return Err(ApiError::from(e));
         }
     };
-    
+
     // Используем выделенный метод для получения деталей
     println!("[Backend] Поиск манги по ID через API...");
-    let manga = match client.manga_detail(id).await {
+    let manga = match requests::send_with(priority, client.manga_detail(id)).await {
         Ok(Some(m)) => m,
         Ok(None) => {
             println!("[Backend] Манга с ID {} не найдена", id);
-            return Err(ApiError {
-                kind: "not_found".to_string(),
-                message: format!("Манга с ID {} не найдена.", id),
-                retry_after: None,
-            });
+            return Err(ApiError::manga_not_found(id));
         }
         Err(e) => {
-            println!("[Backend] Ошибка API при получении деталей манги: {:?}", e);
-            return Err(ApiError::from(e));
+            println!("[Backend] Ошибка API при получении деталей манги: {}", e);
+            return Err(e);
         }
     };
-    
+
     println!("[Backend] Манга найдена: {}. Преобразование данных...", manga.name);
+    let spoiler_mode = get_settings(app_handle).spoiler_mode;
+    let sanitize_options = SanitizeOptions { collapse_spoilers: collapse_spoilers.unwrap_or(false), spoiler_mode };
+    let (description, description_rich) = convert_description(manga.description, spoiler_mode);
     Ok(MangaDetail {
         id: manga.id,
         mal_id: manga.mal_id,
+        titles: Names {
+            title: &manga.name,
+            russian: manga.russian.as_deref(),
+            license_name_ru: manga.license_name_ru.as_deref(),
+            english: manga.english.as_deref(),
+            japanese: manga.japanese.as_deref(),
+        }
+        .titles(),
         title: manga.name,
         russian: manga.russian,
         license_name_ru: manga.license_name_ru,
@@ -1187,8 +2631,9 @@ async fn get_manga_by_id(id: i64) -> Result<MangaDetail, ApiError> {
         synonyms: manga.synonyms,
         url: manga.url.or_else(|| Some(format!("https://shikimori.one/mangas/{}", manga.id))),
         poster_url: manga.poster.and_then(|p| p.main_url),
-        description: manga.description,
-        description_html: manga.description_html,
+        description,
+        description_rich,
+        description_html: manga.description_html.map(|h| html::sanitize(&h, sanitize_options)),
         description_source: manga.description_source,
         score: manga.score,
         kind: manga.kind,
@@ -1201,9 +2646,9 @@ async fn get_manga_by_id(id: i64) -> Result<MangaDetail, ApiError> {
         genres: manga.genres.map(|g| g.into_iter().map(convert_genre).collect()),
         publishers: manga.publishers.map(|p| p.into_iter().map(convert_publisher).collect()),
         external_links: manga.external_links.map(|l| l.into_iter().map(convert_external_link).collect()),
-        person_roles: manga.person_roles.map(|r| r.into_iter().map(convert_person_role).collect()),
-        character_roles: manga.character_roles.map(|r| r.into_iter().map(convert_character_role).collect()),
-        related: manga.related.map(|r| r.into_iter().map(convert_related).collect()),
+        person_roles: manga.person_roles.map(|r| r.into_iter().map(|r| convert_person_role(r, spoiler_mode)).collect()),
+        character_roles: manga.character_roles.map(|r| r.into_iter().map(|r| convert_character_role(r, spoiler_mode)).collect()),
+        related: manga.related.map(|r| r.into_iter().map(|r| convert_related(r, spoiler_mode)).collect()),
         scores_stats: manga.scores_stats.map(|s| s.into_iter().map(convert_score_stat).collect()),
         statuses_stats: manga.statuses_stats.map(|s| s.into_iter().map(convert_status_stat).collect()),
         licensors: manga.licensors,
@@ -1211,92 +2656,300 @@ async fn get_manga_by_id(id: i64) -> Result<MangaDetail, ApiError> {
 }
 
 #[tauri::command]
-async fn search_studios(query: String) -> Result<Vec<Studio>, ApiError> {
-    let client = ShikicrateClient::new().map_err(ApiError::from)?;
-    let studios = client.studios(if query.is_empty() { None } else { Some(query) }).await.map_err(ApiError::from)?;
-    Ok(studios.into_iter().map(convert_studio).collect())
+async fn get_manga_by_id(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    collapse_spoilers: Option<bool>,
+    request_token: Option<String>,
+) -> Result<MangaDetail, ApiError> {
+    let key = response_cache::detail_key("manga", id, collapse_spoilers);
+    if let Some(detail) = response_cache::get(&key) {
+        return Ok(detail);
+    }
+    prefetch::preempt(PrefetchEntity::Manga, id);
+    let detail = requests::run_shared(request_token, key.clone(), load_manga_detail(app_handle, id, collapse_spoilers, Priority::Foreground)).await?;
+    response_cache::insert(key, detail.clone());
+    Ok(detail)
+}
+
+fn send_sections<T: Serialize>(on_event: &tauri::ipc::Channel<DetailEvent<T>>, sections: Vec<Option<DetailSection>>) -> Result<(), ApiError> {
+    for section in sections.into_iter().flatten() {
+        on_event.send(DetailEvent::Section(section)).map_err(ApiError::channel)?;
+    }
+    on_event.send(DetailEvent::Done).map_err(ApiError::channel)
+}
+
+/// Загружает раздел карточки из REST и отправляет его, как только он готов.
+/// Ошибку запроса раздела только логируем: шапка уже показана, остальные
+/// разделы догрузятся без него.
+async fn stream_section<T: Serialize, R: serde::de::DeserializeOwned>(
+    client: &ShikicrateClient,
+    on_event: &tauri::ipc::Channel<DetailEvent<T>>,
+    path: String,
+    into_sections: impl FnOnce(R) -> Vec<DetailSection>,
+) -> Result<(), ApiError> {
+    match requests::send(client.get_rest::<R, ()>(&path, None)).await {
+        Ok(data) => {
+            for section in into_sections(data) {
+                on_event.send(DetailEvent::Section(section)).map_err(ApiError::channel)?;
+            }
+            Ok(())
+        }
+        Err(e) => {
+            println!("[Backend] Не удалось загрузить {}: {}", path, e);
+            Ok(())
+        }
+    }
+}
+
+fn roles_sections(roles: Vec<RestRole>, spoiler_mode: SpoilerMode) -> Vec<DetailSection> {
+    let (characters, people) = convert_rest_roles(roles, spoiler_mode);
+    vec![DetailSection::CharacterRoles(characters), DetailSection::PersonRoles(people)]
+}
+
+fn related_sections(related: Vec<RestRelated>, spoiler_mode: SpoilerMode) -> Vec<DetailSection> {
+    vec![DetailSection::Related(related.into_iter().map(|r| convert_rest_related(r, spoiler_mode)).collect())]
+}
+
+fn external_links_sections(links: Vec<RestExternalLink>) -> Vec<DetailSection> {
+    vec![DetailSection::ExternalLinks(links.into_iter().map(convert_rest_external_link).collect())]
 }
 
+/// Как `get_anime_by_id`, но сначала приходит шапка карточки из лёгкого
+/// REST-запроса, а роли, связанные тайтлы, скриншоты и ссылки загружаются
+/// параллельно и отправляются каждый по готовности.
 #[tauri::command]
-async fn search_publishers(query: String) -> Result<Vec<Publisher>, ApiError> {
-    let client = ShikicrateClient::new().map_err(ApiError::from)?;
-    let publishers = client.publishers(if query.is_empty() { None } else { Some(query) }).await.map_err(ApiError::from)?;
-    Ok(publishers.into_iter().map(convert_publisher).collect())
+async fn stream_anime_detail(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    collapse_spoilers: Option<bool>,
+    request_token: Option<String>,
+    on_event: tauri::ipc::Channel<DetailEvent<AnimeDetail>>,
+) -> Result<(), ApiError> {
+    // Полная карточка уже есть (например, после предзагрузки) — отдаём её без запросов
+    if let Some(mut detail) = response_cache::get::<AnimeDetail>(&response_cache::detail_key("anime", id, collapse_spoilers)) {
+        let sections = vec![
+            detail.character_roles.take().map(DetailSection::CharacterRoles),
+            detail.person_roles.take().map(DetailSection::PersonRoles),
+            detail.related.take().map(DetailSection::Related),
+            detail.screenshots.take().map(DetailSection::Screenshots),
+            detail.external_links.take().map(DetailSection::ExternalLinks),
+        ];
+        on_event.send(DetailEvent::Core(detail)).map_err(ApiError::channel)?;
+        return send_sections(&on_event, sections);
+    }
+    prefetch::preempt(PrefetchEntity::Anime, id);
+    let spoiler_mode = get_settings(app_handle).spoiler_mode;
+    let sanitize_options = SanitizeOptions { collapse_spoilers: collapse_spoilers.unwrap_or(false), spoiler_mode };
+    requests::run(request_token, async move {
+        let client = ShikicrateClient::new().map_err(ApiError::from)?;
+        let path = format!("animes/{}", id);
+        let core = requests::send(client.get_rest::<RestAnimeDetail, ()>(&path, None))
+            .await
+            .map_err(|e| if e.status == Some(404) { ApiError::anime_not_found(id) } else { e })?;
+        on_event.send(DetailEvent::Core(convert_rest_anime(core, sanitize_options))).map_err(ApiError::channel)?;
+
+        let (roles, related, screenshots, links) = tokio::join!(
+            stream_section(&client, &on_event, format!("{}/roles", path), |r| roles_sections(r, spoiler_mode)),
+            stream_section(&client, &on_event, format!("{}/related", path), |r| related_sections(r, spoiler_mode)),
+            stream_section(&client, &on_event, format!("{}/screenshots", path), |s: Vec<RestScreenshot>| {
+                vec![DetailSection::Screenshots(s.into_iter().enumerate().map(convert_rest_screenshot).collect())]
+            }),
+            stream_section(&client, &on_event, format!("{}/external_links", path), external_links_sections),
+        );
+        roles.and(related).and(screenshots).and(links)?;
+        on_event.send(DetailEvent::Done).map_err(ApiError::channel)
+    })
+    .await
+}
+
+/// Потоковый вариант `get_manga_by_id`, см. [`stream_anime_detail`].
+#[tauri::command]
+async fn stream_manga_detail(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    collapse_spoilers: Option<bool>,
+    request_token: Option<String>,
+    on_event: tauri::ipc::Channel<DetailEvent<MangaDetail>>,
+) -> Result<(), ApiError> {
+    if let Some(mut detail) = response_cache::get::<MangaDetail>(&response_cache::detail_key("manga", id, collapse_spoilers)) {
+        let sections = vec![
+            detail.character_roles.take().map(DetailSection::CharacterRoles),
+            detail.person_roles.take().map(DetailSection::PersonRoles),
+            detail.related.take().map(DetailSection::Related),
+            detail.external_links.take().map(DetailSection::ExternalLinks),
+        ];
+        on_event.send(DetailEvent::Core(detail)).map_err(ApiError::channel)?;
+        return send_sections(&on_event, sections);
+    }
+    prefetch::preempt(PrefetchEntity::Manga, id);
+    let spoiler_mode = get_settings(app_handle).spoiler_mode;
+    let sanitize_options = SanitizeOptions { collapse_spoilers: collapse_spoilers.unwrap_or(false), spoiler_mode };
+    requests::run(request_token, async move {
+        let client = ShikicrateClient::new().map_err(ApiError::from)?;
+        let path = format!("mangas/{}", id);
+        let core = requests::send(client.get_rest::<RestMangaDetail, ()>(&path, None))
+            .await
+            .map_err(|e| if e.status == Some(404) { ApiError::manga_not_found(id) } else { e })?;
+        on_event.send(DetailEvent::Core(convert_rest_manga(core, sanitize_options))).map_err(ApiError::channel)?;
+
+        let (roles, related, links) = tokio::join!(
+            stream_section(&client, &on_event, format!("{}/roles", path), |r| roles_sections(r, spoiler_mode)),
+            stream_section(&client, &on_event, format!("{}/related", path), |r| related_sections(r, spoiler_mode)),
+            stream_section(&client, &on_event, format!("{}/external_links", path), external_links_sections),
+        );
+        roles.and(related).and(links)?;
+        on_event.send(DetailEvent::Done).map_err(ApiError::channel)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn search_studios(query: String, request_token: Option<String>) -> Result<Vec<Studio>, ApiError> {
+    requests::run(request_token, async move {
+        let client = ShikicrateClient::new().map_err(ApiError::from)?;
+        let studios = requests::send(client.studios(if query.is_empty() { None } else { Some(query) })).await?;
+        Ok(studios.into_iter().map(convert_studio).collect())
+    })
+    .await
+}
+
+#[tauri::command]
+async fn search_publishers(query: String, request_token: Option<String>) -> Result<Vec<Publisher>, ApiError> {
+    requests::run(request_token, async move {
+        let client = ShikicrateClient::new().map_err(ApiError::from)?;
+        let publishers = requests::send(client.publishers(if query.is_empty() { None } else { Some(query) })).await?;
+        Ok(publishers.into_iter().map(convert_publisher).collect())
+    })
+    .await
 }
 
 #[tauri::command]
-async fn get_genres() -> Result<Vec<Genre>, ApiError> {
+async fn get_genres() -> Result<GenreCatalog, ApiError> {
     let client = ShikicrateClient::new().map_err(ApiError::from)?;
-    let genres = client.genres().await.map_err(ApiError::from)?;
-    Ok(genres.into_iter().map(convert_genre).collect())
+    let genres = requests::send(client.genres()).await?;
+
+    // GraphQL не говорит, к аниме или манге относится жанр, это знает только REST
+    let rest_genres = requests::send(client.get_rest::<Vec<RestGenre>, ()>("genres", None))
+        .await
+        .unwrap_or_else(|e| {
+            println!("[Backend] Не удалось получить жанры через REST: {}", e);
+            Vec::new()
+        });
+
+    let mut catalog = GenreCatalog::default();
+    let mut seen = std::collections::HashSet::new();
+    for genre in genres.into_iter().map(convert_genre) {
+        let is_manga = rest_genres
+            .iter()
+            .find(|g| g.id == genre.id)
+            .is_some_and(|g| g.entry_type.as_deref() == Some("Manga"));
+        seen.insert(genre.id);
+        if is_manga { catalog.manga.push(genre) } else { catalog.anime.push(genre) }
+    }
+    for g in rest_genres.into_iter().filter(|g| !seen.contains(&g.id)) {
+        let genre = Genre {
+            label: g.russian.clone().unwrap_or_else(|| g.name.clone()),
+            id: g.id,
+            name: g.name,
+            russian: g.russian,
+            kind: g.kind.filter(|k| k != "anime" && k != "manga"),
+        };
+        if g.entry_type.as_deref() == Some("Manga") { catalog.manga.push(genre) } else { catalog.anime.push(genre) }
+    }
+
+    catalog.anime.sort();
+    catalog.manga.sort();
+    Ok(catalog)
 }
 
 #[tauri::command]
-async fn get_accent_color(url: String) -> Result<String, String> {
+fn get_filter_options() -> filters::FilterOptions {
+    filters::filter_options()
+}
+
+fn image_fetch_error(e: reqwest::Error) -> ApiError {
+    let error = ApiError::new(ErrorCode::ImageFetch, t("error.image_fetch").to_string());
+    match e.status() {
+        Some(status) => error.with_status(status.as_u16()).caused_by(e),
+        None => error.caused_by(e),
+    }
+}
+
+#[tauri::command]
+async fn get_accent_color(url: String) -> Result<String, ApiError> {
     // 1. Проверка кэша
     let cache = ACCENT_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
     if let Some(color) = cache.lock().unwrap().get(&url) {
         return Ok(color.clone());
     }
 
-    // 2. Инициализация клиента
-    let client = HTTP_CLIENT.get_or_init(|| {
-        reqwest::Client::builder()
-            .timeout(Duration::from_secs(5))
-            .build()
-            .expect("Failed to build HTTP client")
-    });
+    // Одну и ту же обложку часто запрашивают несколько карточек одновременно
+    let key = format!("accent_color:{}", url);
+    single_flight::coalesce(key, async move {
+        // 2. Инициализация клиента
+        let client = HTTP_CLIENT.get_or_init(|| {
+            reqwest::Client::builder()
+                .timeout(Duration::from_secs(5))
+                .build()
+                .expect("Failed to build HTTP client")
+        });
 
-    // 3. Выполнение запроса
-    let response = client.get(&url)
-        .send()
-        .await
-        .map_err(|e| format!("Ошибка сети: {}", e))?;
+        // 3. Выполнение запроса
+        let response = client.get(&url)
+            .send()
+            .await
+            .map_err(image_fetch_error)?;
 
-    // Проверка размера контента (макс 2МБ)
-    if let Some(len) = response.content_length() {
-        if len > 2 * 1024 * 1024 {
-            return Ok("rgba(180, 160, 120, 0.9)".to_string());
+        // Проверка размера контента (макс 2МБ)
+        if let Some(len) = response.content_length() {
+            if len > 2 * 1024 * 1024 {
+                return Ok("rgba(180, 160, 120, 0.9)".to_string());
+            }
         }
-    }
-
-    let bytes = response.bytes().await.map_err(|e| format!("Ошибка загрузки: {}", e))?;
 
-    let img = image::load_from_memory(&bytes).map_err(|e| format!("Ошибка декодирования: {}", e))?;
-    let img = img.thumbnail(10, 10);
-    let rgb = img.to_rgb8();
-
-    let mut r: u32 = 0;
-    let mut g: u32 = 0;
-    let mut b: u32 = 0;
-    let mut count: u32 = 0;
-
-    for pixel in rgb.pixels() {
-        let brightness = (pixel[0] as f32 * 0.299 + pixel[1] as f32 * 0.587 + pixel[2] as f32 * 0.114) as f32;
-        if brightness > 30.0 && brightness < 220.0 {
-            r += pixel[0] as u32;
-            g += pixel[1] as u32;
-            b += pixel[2] as u32;
-            count += 1;
+        let bytes = response.bytes().await.map_err(image_fetch_error)?;
+
+        let img = image::load_from_memory(&bytes).map_err(|e| {
+            ApiError::new(ErrorCode::ImageDecode, t("error.image_decode").to_string())
+                .caused_by(e)
+        })?;
+        let img = img.thumbnail(10, 10);
+        let rgb = img.to_rgb8();
+
+        let mut r: u32 = 0;
+        let mut g: u32 = 0;
+        let mut b: u32 = 0;
+        let mut count: u32 = 0;
+
+        for pixel in rgb.pixels() {
+            let brightness = (pixel[0] as f32 * 0.299 + pixel[1] as f32 * 0.587 + pixel[2] as f32 * 0.114) as f32;
+            if brightness > 30.0 && brightness < 220.0 {
+                r += pixel[0] as u32;
+                g += pixel[1] as u32;
+                b += pixel[2] as u32;
+                count += 1;
+            }
         }
-    }
 
-    let result_color = if count == 0 {
-        "rgba(180, 160, 120, 0.9)".to_string()
-    } else {
-        let factor = 0.8;
-        format!(
-            "rgba({}, {}, {}, 0.9)",
-            ((r / count) as f32 * factor) as u8,
-            ((g / count) as f32 * factor) as u8,
-            ((b / count) as f32 * factor) as u8
-        )
-    };
+        let result_color = if count == 0 {
+            "rgba(180, 160, 120, 0.9)".to_string()
+        } else {
+            let factor = 0.8;
+            format!(
+                "rgba({}, {}, {}, 0.9)",
+                ((r / count) as f32 * factor) as u8,
+                ((g / count) as f32 * factor) as u8,
+                ((b / count) as f32 * factor) as u8
+            )
+        };
 
-    // Сохранение в кэш
-    cache.lock().unwrap().insert(url, result_color.clone());
+        // Сохранение в кэш
+        cache.lock().unwrap().insert(url, result_color.clone());
     
-    Ok(result_color)
+        Ok(result_color)
+    })
+    .await
 }
 
 fn main() {
@@ -1312,13 +2965,38 @@ fn main() {
             search_people,
             search_studios,
             search_publishers,
+            search_all,
+            get_animes_by_ids,
+            get_mangas_by_ids,
+            get_characters_by_ids,
             get_genres,
+            get_filter_options,
             get_anime_by_id,
             get_manga_by_id,
+            stream_anime_detail,
+            stream_manga_detail,
             get_character_details,
             get_accent_color,
             get_settings,
-            update_settings
+            update_settings,
+            record_search,
+            get_search_history,
+            delete_search_history_entry,
+            clear_search_history,
+            get_search_suggestions,
+            create_saved_search,
+            get_saved_searches,
+            delete_saved_search,
+            check_saved_searches,
+            cancel_request,
+            prefetch,
+            get_translations,
+            export_settings,
+            import_settings,
+            list_profiles,
+            save_profile,
+            delete_profile,
+            switch_profile
         ])
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
@@ -1332,16 +3010,16 @@ fn main() {
         })
         .setup(|app| {
             println!("Tauri приложение инициализировано");
+            // Хранилище создаётся до первой команды и до первого события окна
+            app.manage(SettingsStore::open(get_config_path(app.handle())?));
+            apply_settings(app.handle(), None, &get_settings(app.handle().clone()));
             
             // Настройка системного трея
             use tauri::tray::{TrayIconBuilder, MouseButton, MouseButtonState};
-            use tauri::menu::{Menu, MenuItem};
 
-            let quit_i = MenuItem::with_id(app, "quit", "Выход", true, None::<&str>).unwrap();
-            let show_i = MenuItem::with_id(app, "show", "Показать", true, None::<&str>).unwrap();
-            let menu = Menu::with_items(app, &[&show_i, &quit_i]).unwrap();
+            let menu = tray_menu(app.handle()).unwrap();
 
-            let _tray = TrayIconBuilder::new()
+            let _tray = TrayIconBuilder::with_id(TRAY_ID)
                 .icon(app.default_window_icon().unwrap().clone())
                 .menu(&menu)
                 .show_menu_on_left_click(false)
@@ -1374,8 +3052,175 @@ fn main() {
                 .build(app)
                 .unwrap();
 
+            saved_searches::start_scheduler(app.handle().clone());
+
             Ok(())
         })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rest_character() -> RestCharacter {
+        serde_json::from_value(serde_json::json!({
+            "name": "Rest Name",
+            "russian": "Рест",
+            "japanese": "日本",
+            "altname": "Alt, Other",
+            "image": { "original": "/images/character/original/1.jpg", "preview": null, "x96": null, "x48": null },
+            "url": "/characters/1",
+            "description": "rest description",
+            "description_html": "<b>rest</b>",
+            "seyu": [{ "id": "5", "name": "Seyu", "russian": null, "image": null, "url": "/people/5" }],
+            "animes": [{ "id": 10, "name": "Anime", "score": "8.5", "roles": ["Main"] }],
+            "mangas": [{ "id": 20, "name": "Manga", "score": null, "roles": ["Supporting"] }],
+        }))
+        .unwrap()
+    }
+
+    fn graphql_character() -> GraphqlCharacter {
+        GraphqlCharacter {
+            name: "Graph Name".to_string(),
+            russian: Some("Граф".to_string()),
+            url: None,
+            poster: Some(Poster {
+                main: Some("https://shikimori.one/main.jpg".to_string()),
+                original: None,
+                preview: None,
+                x96: None,
+                x48: None,
+            }),
+            description: Some("graph description".to_string()),
+        }
+    }
+
+    #[test]
+    fn merged_character_takes_scalars_from_graphql_and_lists_from_rest() {
+        let detail = merge_character(1, Some(graphql_character()), Some(rest_character()), DetailSource::Merged, SanitizeOptions::default());
+
+        assert_eq!(detail.name, "Graph Name");
+        assert_eq!(detail.russian.as_deref(), Some("Граф"));
+        assert_eq!(detail.poster_url.as_deref(), Some("https://shikimori.one/main.jpg"));
+        assert_eq!(detail.description.as_deref(), Some("graph description"));
+        assert_eq!(detail.japanese.as_deref(), Some("日本"));
+        assert_eq!(detail.synonyms, vec!["Alt", "Other"]);
+        assert_eq!(detail.url.as_deref(), Some("https://shikimori.one/characters/1"));
+        assert_eq!(detail.description_html.as_deref(), Some("<b>rest</b>"));
+
+        let anime = detail.character_roles[0].anime.as_ref().unwrap();
+        assert_eq!((anime.id, anime.score), (10, Some(8.5)));
+        assert_eq!(detail.character_roles[0].roles_ru, vec!["Main"]);
+        assert_eq!(detail.character_roles[1].manga.as_ref().map(|m| m.id), Some(20));
+        assert_eq!(detail.seyus.iter().map(|s| s.id).collect::<Vec<_>>(), vec![5]);
+        assert_eq!(detail.source, DetailSource::Merged);
+    }
+
+    #[test]
+    fn rest_only_character_uses_rest_scalars() {
+        let detail = merge_character(1, None, Some(rest_character()), DetailSource::Rest, SanitizeOptions::default());
+
+        assert_eq!(detail.name, "Rest Name");
+        assert_eq!(detail.russian.as_deref(), Some("Рест"));
+        assert_eq!(detail.description.as_deref(), Some("rest description"));
+        // Размера main у REST нет, берётся ближайший больший
+        assert_eq!(detail.poster_url.as_deref(), Some("https://shikimori.one/images/character/original/1.jpg"));
+        assert_eq!(detail.character_roles.len(), 2);
+    }
+
+    #[test]
+    fn graphql_only_character_has_no_roles() {
+        let detail = merge_character(1, Some(graphql_character()), None, DetailSource::Graphql, SanitizeOptions::default());
+
+        assert_eq!(detail.name, "Graph Name");
+        assert!(detail.character_roles.is_empty() && detail.seyus.is_empty());
+        assert_eq!(detail.description_html, None);
+    }
+
+    #[test]
+    fn rest_dates_are_split_into_parts() {
+        let date = rest_date(Some("2002-10-03".to_string())).unwrap();
+        assert_eq!((date.year, date.month, date.day), (Some(2002), Some(10), Some(3)));
+        assert_eq!(date.date.as_deref(), Some("2002-10-03"));
+        assert!(rest_date(None).is_none());
+    }
+
+    #[test]
+    fn rest_roles_are_split_into_characters_and_people() {
+        let roles: Vec<RestRole> = serde_json::from_value(serde_json::json!([
+            { "roles": ["Main"], "roles_russian": ["Main"], "character": { "id": 1, "name": "Naruto", "russian": "Наруто", "image": null }, "person": null },
+            { "roles": ["Director"], "roles_russian": ["Режиссёр"], "character": null, "person": { "id": "2", "name": "Date", "russian": null, "image": null } },
+        ]))
+        .unwrap();
+
+        let (characters, people) = convert_rest_roles(roles, SpoilerMode::Show);
+
+        assert_eq!(characters.len(), 1);
+        assert_eq!(characters[0].character.russian.as_deref(), Some("Наруто"));
+        assert_eq!(people.len(), 1);
+        assert_eq!(people[0].person.id, 2);
+        assert_eq!(people[0].roles_ru, Some(vec!["Режиссёр".to_string()]));
+    }
+
+    #[test]
+    fn rest_relation_kinds_match_graphql_keys() {
+        let related: RestRelated = serde_json::from_value(serde_json::json!({
+            "relation": "Side story",
+            "relation_russian": "Другая история",
+            "anime": { "id": 7, "name": "Side", "russian": null, "image": null },
+            "manga": null,
+        }))
+        .unwrap();
+
+        let related = convert_rest_related(related, SpoilerMode::Show);
+
+        assert_eq!(related.relation_kind, "side_story");
+        assert_eq!(related.id, 7);
+        assert_eq!(related.relation_text.as_deref(), Some("Другая история"));
+    }
+
+    #[test]
+    fn rest_status_labels_become_graphql_keys() {
+        let stat = rest_status_stat(RestStatusStat { name: "Читаю".to_string(), value: 3 });
+        assert_eq!((stat.status.as_str(), stat.count), ("watching", 3));
+    }
+
+    #[test]
+    fn people_page_slices_filtered_results() {
+        let page = people_page((1..=7).collect(), 7, 2, 3);
+        assert_eq!(page.items, vec![4, 5, 6]);
+        assert!(page.has_next_page);
+
+        let last = people_page((1..=7).collect(), 7, 3, 3);
+        assert_eq!(last.items, vec![7]);
+        assert_eq!(last.total, Some(7));
+    }
+
+    #[test]
+    fn people_page_at_api_cap_has_unknown_total() {
+        let people: Vec<u32> = (1..=paging::API_MAX_LIMIT).collect();
+        let last = people_page(people, paging::API_MAX_LIMIT as usize, 3, 20);
+        assert_eq!(last.items.len(), 10);
+        assert!(!last.has_next_page);
+        assert_eq!(last.total, None);
+    }
+
+    fn genre(id: i64, name: &str, kind: Option<&str>) -> Genre {
+        Genre { id, name: name.to_string(), russian: None, kind: kind.map(str::to_string), label: name.to_string() }
+    }
+
+    #[test]
+    fn genres_are_grouped_by_kind_and_sorted() {
+        let mut groups = GenreGroups::default();
+        for g in [genre(1, "Romance", Some("genre")), genre(2, "Action", None), genre(3, "School", Some("theme")), genre(4, "Shounen", Some("demographic"))] {
+            groups.push(g);
+        }
+        groups.sort();
+
+        assert_eq!(groups.genres.iter().map(|g| g.id).collect::<Vec<_>>(), vec![2, 1]);
+        assert_eq!(groups.themes.len(), 1);
+        assert_eq!(groups.demographics[0].id, 4);
+    }
+}
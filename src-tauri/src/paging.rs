@@ -0,0 +1,91 @@
+//! Определение следующей страницы без лишнего запроса. API принимает только
+//! `page` и `limit`, поэтому «`limit + 1` элементов с начала страницы» получаем,
+//! подобрав размер страницы API, при котором это окно целиком попадает в одну её страницу.
+
+/// Максимальный `limit`, который принимает API.
+pub const API_MAX_LIMIT: u32 = 50;
+
+/// Страница API, из которой вырезается запрошенная страница.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Window {
+    pub api_page: u32,
+    pub api_limit: u32,
+    skip: usize,
+    lookahead: bool,
+}
+
+/// Подбирает наименьший размер страницы API, при котором элементы запрошенной
+/// страницы и следующий за ними попадают в одну страницу API. Если такого нет,
+/// запрашивается сама страница, а о следующей судим по её заполненности.
+pub fn window(page: u32, limit: u32) -> Window {
+    let start = page.saturating_sub(1) as u64 * limit as u64;
+    let lookahead = start + limit as u64;
+    for api_limit in (limit + 1)..=API_MAX_LIMIT {
+        let size = api_limit as u64;
+        if start / size == lookahead / size {
+            return Window {
+                api_page: (start / size) as u32 + 1,
+                api_limit,
+                skip: (start % size) as usize,
+                lookahead: true,
+            };
+        }
+    }
+    Window { api_page: page.max(1), api_limit: limit, skip: 0, lookahead: false }
+}
+
+impl Window {
+    /// Вырезает запрошенную страницу из выдачи API и сообщает, есть ли следующая.
+    pub fn split<T>(&self, items: Vec<T>, limit: u32) -> (Vec<T>, bool) {
+        let full = items.len() == self.api_limit as usize;
+        let mut page: Vec<T> = items.into_iter().skip(self.skip).collect();
+        let has_next = if self.lookahead { page.len() > limit as usize } else { full };
+        page.truncate(limit as usize);
+        (page, has_next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_page_fetches_one_extra() {
+        assert_eq!(window(1, 20), Window { api_page: 1, api_limit: 21, skip: 0, lookahead: true });
+        assert_eq!(window(0, 20), window(1, 20));
+    }
+
+    #[test]
+    fn later_pages_fit_into_one_api_page() {
+        assert_eq!(window(2, 20), Window { api_page: 1, api_limit: 41, skip: 20, lookahead: true });
+        assert_eq!(window(3, 20), Window { api_page: 2, api_limit: 31, skip: 9, lookahead: true });
+    }
+
+    #[test]
+    fn falls_back_to_plain_page_when_nothing_fits() {
+        assert_eq!(window(1, 50), Window { api_page: 1, api_limit: 50, skip: 0, lookahead: false });
+        assert_eq!(window(2, 30), Window { api_page: 2, api_limit: 30, skip: 0, lookahead: false });
+    }
+
+    #[test]
+    fn split_with_lookahead() {
+        let window = window(3, 20);
+        let (page, has_next) = window.split((0..31).collect(), 20);
+        assert_eq!(page, (9..29).collect::<Vec<_>>());
+        assert!(has_next);
+
+        let (page, has_next) = window.split((0..25).collect(), 20);
+        assert_eq!(page, (9..25).collect::<Vec<_>>());
+        assert!(!has_next);
+
+        let (page, has_next) = window.split(Vec::<i32>::new(), 20);
+        assert!(page.is_empty() && !has_next);
+    }
+
+    #[test]
+    fn split_without_lookahead_uses_fullness() {
+        let window = window(2, 30);
+        assert_eq!(window.split((0..30).collect::<Vec<_>>(), 30), ((0..30).collect(), true));
+        assert_eq!(window.split((0..29).collect::<Vec<_>>(), 30), ((0..29).collect(), false));
+    }
+}
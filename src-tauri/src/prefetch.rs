@@ -0,0 +1,191 @@
+//! Фоновая предзагрузка деталей аниме и манги в кэш ответов, пока пользователь
+//! наводит курсор на карточку или прокручивает выдачу. Задания выполняются по
+//! одному из очереди с приоритетом и уступают слот запросам пользователя.
+//!
+//! Запрос пользователя никогда не ждёт предзагрузку той же карточки: у неё свой
+//! ключ объединения запросов, а [`preempt`] отменяет её и убирает из очереди.
+
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde::Deserialize;
+use tokio::sync::Notify;
+
+use crate::rate_limit::Priority;
+use crate::{response_cache, ApiError};
+
+/// Сколько заданий держать в очереди; при переполнении отбрасываются наименее важные.
+const MAX_QUEUE: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrefetchEntity {
+    Anime,
+    Manga,
+}
+
+impl PrefetchEntity {
+    fn name(self) -> &'static str {
+        match self {
+            PrefetchEntity::Anime => "anime",
+            PrefetchEntity::Manga => "manga",
+        }
+    }
+}
+
+/// Карточка под курсором важнее карточки, которая просто видна на экране.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrefetchPriority {
+    #[default]
+    Visible,
+    Hover,
+}
+
+// Порядок полей задаёт порядок в очереди: приоритет, затем более свежее задание
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Job {
+    priority: PrefetchPriority,
+    seq: u64,
+    entity: PrefetchEntity,
+    id: i64,
+}
+
+#[derive(Default)]
+struct Queue {
+    jobs: BinaryHeap<Job>,
+    next_seq: u64,
+}
+
+/// Задание, которое выполняется сейчас, и сигнал его отмены.
+struct Running {
+    entity: PrefetchEntity,
+    id: i64,
+    cancel: Arc<Notify>,
+}
+
+static QUEUE: Mutex<Option<Queue>> = Mutex::new(None);
+static RUNNING: Mutex<Option<Running>> = Mutex::new(None);
+static WAKE: OnceLock<Notify> = OnceLock::new();
+static WORKER: OnceLock<()> = OnceLock::new();
+
+fn wake() -> &'static Notify {
+    WAKE.get_or_init(Notify::new)
+}
+
+/// Ставит ID в очередь предзагрузки. Уже закэшированные пропускаются,
+/// повторно запрошенные поднимаются в очереди.
+pub fn schedule(app_handle: &tauri::AppHandle, entity: PrefetchEntity, ids: Vec<i64>, priority: PrefetchPriority) {
+    let ids: Vec<i64> = ids
+        .into_iter()
+        .filter(|id| !response_cache::contains(&response_cache::detail_key(entity.name(), *id, None)))
+        .collect();
+    if ids.is_empty() {
+        return;
+    }
+
+    {
+        let mut guard = QUEUE.lock().unwrap();
+        let queue = guard.get_or_insert_with(Queue::default);
+        queue.jobs.retain(|job| !(job.entity == entity && ids.contains(&job.id)));
+        for id in ids {
+            queue.next_seq += 1;
+            queue.jobs.push(Job { priority, seq: queue.next_seq, entity, id });
+        }
+        if queue.jobs.len() > MAX_QUEUE {
+            let mut jobs = std::mem::take(&mut queue.jobs).into_sorted_vec();
+            jobs.drain(..jobs.len() - MAX_QUEUE);
+            queue.jobs = jobs.into();
+        }
+    }
+
+    WORKER.get_or_init(|| {
+        tauri::async_runtime::spawn(work(app_handle.clone()));
+    });
+    wake().notify_one();
+}
+
+/// Вызывается перед запросом пользователя: отменяет предзагрузку той же
+/// карточки, если она идёт, и убирает её из очереди.
+pub fn preempt(entity: PrefetchEntity, id: i64) {
+    if let Some(queue) = QUEUE.lock().unwrap().as_mut() {
+        queue.jobs.retain(|job| !(job.entity == entity && job.id == id));
+    }
+    if let Some(running) = RUNNING.lock().unwrap().as_ref() {
+        if running.entity == entity && running.id == id {
+            running.cancel.notify_one();
+        }
+    }
+}
+
+fn next_job() -> Option<Job> {
+    QUEUE.lock().unwrap().as_mut().and_then(|queue| queue.jobs.pop())
+}
+
+async fn work(app_handle: tauri::AppHandle) {
+    loop {
+        let Some(job) = next_job() else {
+            wake().notified().await;
+            continue;
+        };
+
+        let key = response_cache::detail_key(job.entity.name(), job.id, None);
+        if response_cache::contains(&key) {
+            continue;
+        }
+
+        let cancel = Arc::new(Notify::new());
+        *RUNNING.lock().unwrap() = Some(Running { entity: job.entity, id: job.id, cancel: cancel.clone() });
+
+        // Запросы деталей берут фоновый слот лимита и уступают запросам пользователя
+        match job.entity {
+            PrefetchEntity::Anime => load(key, &cancel, crate::load_anime_detail(app_handle.clone(), job.id, None, Priority::Background)).await,
+            PrefetchEntity::Manga => load(key, &cancel, crate::load_manga_detail(app_handle.clone(), job.id, None, Priority::Background)).await,
+        }
+        *RUNNING.lock().unwrap() = None;
+    }
+}
+
+async fn load<T, F>(key: String, cancel: &Notify, detail: F)
+where
+    T: Send + 'static,
+    F: std::future::Future<Output = Result<T, ApiError>>,
+{
+    tokio::select! {
+        result = detail => match result {
+            Ok(detail) => response_cache::insert(key, detail),
+            Err(e) => println!("[Backend] Предзагрузка {} не удалась: {}", key, e),
+        },
+        _ = cancel.notified() => println!("[Backend] Предзагрузка {} отменена запросом пользователя", key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn preempt_cancels_running_job_and_drops_queued() {
+        let cancel = Arc::new(Notify::new());
+        *RUNNING.lock().unwrap() = Some(Running { entity: PrefetchEntity::Anime, id: 1, cancel: cancel.clone() });
+        {
+            let mut guard = QUEUE.lock().unwrap();
+            let queue = guard.get_or_insert_with(Queue::default);
+            for (seq, (entity, id)) in [(PrefetchEntity::Anime, 1), (PrefetchEntity::Manga, 1), (PrefetchEntity::Anime, 2)].into_iter().enumerate() {
+                queue.jobs.push(Job { priority: PrefetchPriority::Visible, seq: seq as u64, entity, id });
+            }
+        }
+
+        preempt(PrefetchEntity::Manga, 2);
+        preempt(PrefetchEntity::Anime, 1);
+
+        let cancelled = tauri::async_runtime::block_on(tokio::time::timeout(Duration::from_secs(1), cancel.notified()));
+        assert!(cancelled.is_ok());
+        let mut left: Vec<(PrefetchEntity, i64)> = std::iter::from_fn(next_job).map(|job| (job.entity, job.id)).collect();
+        left.sort();
+        assert_eq!(left, vec![(PrefetchEntity::Anime, 2), (PrefetchEntity::Manga, 1)]);
+        *RUNNING.lock().unwrap() = None;
+    }
+}
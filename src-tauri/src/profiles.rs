@@ -0,0 +1,109 @@
+//! Именованные профили настроек: переносимые файлы в каталоге `profiles`
+//! рядом с `config.json`, по одному на профиль.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::t;
+use crate::settings::{self, AppSettings};
+use crate::ApiError;
+
+pub const PROFILES_DIR: &str = "profiles";
+const MAX_NAME_LEN: usize = 64;
+
+/// Имя профиля становится именем файла, поэтому допускаются только буквы,
+/// цифры, пробел, `-` и `_`.
+fn profile_path(dir: &Path, name: &str) -> Result<PathBuf, ApiError> {
+    let name = name.trim();
+    let valid = !name.is_empty()
+        && name.chars().count() <= MAX_NAME_LEN
+        && name.chars().all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'));
+    if !valid {
+        return Err(ApiError::validation(t("validation.profile_name").to_string()));
+    }
+    Ok(dir.join(format!("{}.json", name)))
+}
+
+/// Имена сохранённых профилей по алфавиту.
+pub fn list(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| path.file_stem()?.to_str().map(str::to_string))
+        .collect();
+    names.sort();
+    names
+}
+
+pub fn save(dir: &Path, name: &str, settings: &AppSettings) -> Result<(), ApiError> {
+    let path = profile_path(dir, name)?;
+    fs::create_dir_all(dir).map_err(ApiError::storage)?;
+    settings::write_portable(&path, settings).map_err(ApiError::storage)
+}
+
+pub fn load(dir: &Path, name: &str) -> Result<AppSettings, ApiError> {
+    let path = profile_path(dir, name)?;
+    if !path.exists() {
+        return Err(ApiError::profile_not_found(name.trim()));
+    }
+    settings::read_portable(&path)
+}
+
+pub fn remove(dir: &Path, name: &str) -> Result<(), ApiError> {
+    let path = profile_path(dir, name)?;
+    if !path.exists() {
+        return Err(ApiError::profile_not_found(name.trim()));
+    }
+    fs::remove_file(path).map_err(ApiError::storage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorCode;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("shikimore-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn profiles_round_trip_and_are_listed_by_name() {
+        let dir = temp_dir("profiles-round-trip");
+        let work = AppSettings { nsfw: true, accent_color: "#abc".to_string(), ..AppSettings::default() };
+        save(&dir, " work ", &work).unwrap();
+        save(&dir, "home", &AppSettings::default()).unwrap();
+
+        assert_eq!(list(&dir), vec!["home".to_string(), "work".to_string()]);
+        assert_eq!(load(&dir, "work").unwrap(), work);
+
+        remove(&dir, "home").unwrap();
+        assert_eq!(load(&dir, "home").unwrap_err().code, ErrorCode::ProfileNotFound);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn names_that_escape_the_directory_are_rejected() {
+        let dir = temp_dir("profiles-names");
+        let long = "a".repeat(MAX_NAME_LEN + 1);
+        for name in ["", "../config", "a/b", "x.json", long.as_str()] {
+            assert_eq!(save(&dir, name, &AppSettings::default()).unwrap_err().code, ErrorCode::InvalidParameter, "{:?}", name);
+        }
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn foreign_or_damaged_files_are_not_applied() {
+        let dir = temp_dir("profiles-foreign");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("other.json"), r#"{"format": "something-else", "settings": {}}"#).unwrap();
+        fs::write(dir.join("broken.json"), r#"{"format": "shikimore-settings", "settings": {"version": 2, "nsfw": "yes"}}"#).unwrap();
+
+        assert_eq!(load(&dir, "other").unwrap_err().code, ErrorCode::InvalidParameter);
+        assert_eq!(load(&dir, "broken").unwrap_err().code, ErrorCode::InvalidParameter);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
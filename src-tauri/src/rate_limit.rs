@@ -0,0 +1,104 @@
+//! Общий для всех команд лимит запросов к Shikimori: не больше нескольких
+//! одновременно и не чаще заданного интервала. Слот берётся на каждый
+//! HTTP-запрос и освобождается, когда запрос завершается или отменяется.
+//!
+//! Фоновые запросы (предзагрузка) получают слот, только когда его не ждёт ни
+//! один запрос пользователя, и не отправляются, пока API отвечает `rate_limit`.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio::time::Instant;
+
+/// Shikimori разрешает 5 запросов в секунду.
+const MAX_CONCURRENT: usize = 5;
+const MIN_INTERVAL: Duration = Duration::from_millis(200);
+/// Пауза фоновых запросов после `rate_limit`, если API не сообщил свою.
+const DEFAULT_PAUSE: Duration = Duration::from_secs(5);
+const BACKGROUND_POLL: Duration = Duration::from_millis(100);
+
+/// Чей запрос: пользователя или фоновой предзагрузки.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    Foreground,
+    Background,
+}
+
+static SLOTS: OnceLock<Semaphore> = OnceLock::new();
+static NEXT_START: Mutex<Option<Instant>> = Mutex::new(None);
+static PAUSED_UNTIL: Mutex<Option<Instant>> = Mutex::new(None);
+static FOREGROUND_WAITING: AtomicUsize = AtomicUsize::new(0);
+
+fn slots() -> &'static Semaphore {
+    SLOTS.get_or_init(|| Semaphore::new(MAX_CONCURRENT))
+}
+
+async fn wait_turn() {
+    let start = {
+        let mut next = NEXT_START.lock().unwrap();
+        let start = next.map_or_else(Instant::now, |n| n.max(Instant::now()));
+        *next = Some(start + MIN_INTERVAL);
+        start
+    };
+    tokio::time::sleep_until(start).await;
+}
+
+// Счётчик ожидающих запросов пользователя; уменьшается и при отмене ожидания
+struct Waiting;
+
+impl Waiting {
+    fn new() -> Self {
+        FOREGROUND_WAITING.fetch_add(1, Ordering::SeqCst);
+        Waiting
+    }
+}
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        FOREGROUND_WAITING.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Ждёт свободный слот и свою очередь по времени. Слот занят, пока жив результат.
+pub async fn acquire() -> SemaphorePermit<'static> {
+    let waiting = Waiting::new();
+    let permit = slots().acquire().await.expect("rate limit semaphore is never closed");
+    drop(waiting);
+    wait_turn().await;
+    permit
+}
+
+/// Слот для фонового запроса: уступает запросам пользователя и ждёт окончания паузы.
+pub async fn acquire_background() -> SemaphorePermit<'static> {
+    loop {
+        let paused_until = *PAUSED_UNTIL.lock().unwrap();
+        if let Some(until) = paused_until.filter(|until| *until > Instant::now()) {
+            tokio::time::sleep_until(until).await;
+            continue;
+        }
+        if FOREGROUND_WAITING.load(Ordering::SeqCst) == 0 {
+            if let Ok(permit) = slots().try_acquire() {
+                wait_turn().await;
+                return permit;
+            }
+        }
+        tokio::time::sleep(BACKGROUND_POLL).await;
+    }
+}
+
+/// Слот для запроса с приоритетом `priority`.
+pub async fn acquire_with(priority: Priority) -> SemaphorePermit<'static> {
+    match priority {
+        Priority::Foreground => acquire().await,
+        Priority::Background => acquire_background().await,
+    }
+}
+
+/// Приостанавливает фоновые запросы после ответа `rate_limit`.
+pub fn pause(retry_after: Option<u64>) {
+    let until = Instant::now() + retry_after.map_or(DEFAULT_PAUSE, Duration::from_secs);
+    let mut paused = PAUSED_UNTIL.lock().unwrap();
+    *paused = Some(paused.map_or(until, |p| p.max(until)));
+}
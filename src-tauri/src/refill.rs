@@ -0,0 +1,148 @@
+//! Постраничная выдача с фильтрацией на стороне бэкенда. Когда часть фильтров
+//! не поддерживается API, страницы API отфильтровываются и добираются следующими,
+//! чтобы фронтенд всё равно получал полные страницы.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+
+use crate::ApiError;
+
+/// Сколько страниц API можно запросить за один вызов, прежде чем вернуть то, что есть.
+const MAX_API_PAGES_PER_CALL: u32 = 10;
+const MAX_TRACKED_QUERIES: usize = 100;
+
+/// Позиция в выдаче API, с которой начинается отфильтрованная страница.
+#[derive(Debug, Clone, Copy)]
+struct Cursor {
+    api_page: u32,
+    skip: usize,
+}
+
+// Для каждого набора параметров запоминаем, где начинается каждая отфильтрованная
+// страница, чтобы следующая страница не перечитывала выдачу с начала.
+static CURSORS: OnceLock<Mutex<HashMap<String, HashMap<u32, Cursor>>>> = OnceLock::new();
+
+fn known_cursor(key: &str, page: u32) -> (u32, Cursor) {
+    let cursors = CURSORS.get_or_init(|| Mutex::new(HashMap::new()));
+    let cursors = cursors.lock().unwrap();
+    cursors
+        .get(key)
+        .and_then(|pages| pages.iter().filter(|(p, _)| **p <= page).max_by_key(|(p, _)| **p))
+        .map(|(p, c)| (*p, *c))
+        .unwrap_or((1, Cursor { api_page: 1, skip: 0 }))
+}
+
+fn remember_cursor(key: &str, page: u32, cursor: Cursor) {
+    let cursors = CURSORS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut cursors = cursors.lock().unwrap();
+    if cursors.len() >= MAX_TRACKED_QUERIES && !cursors.contains_key(key) {
+        cursors.clear();
+    }
+    cursors.entry(key.to_string()).or_default().insert(page, cursor);
+}
+
+/// Возвращает `page`-ю страницу из `limit` отфильтрованных элементов и признак
+/// того, что за ней есть ещё элементы.
+/// `fetch` загружает страницу API с тем же `limit` и возвращает `None` на месте
+/// отброшенных элементов; `key` описывает все параметры, кроме номера страницы.
+pub async fn filtered_page<T, F, Fut>(key: &str, page: u32, limit: u32, mut fetch: F) -> Result<(Vec<T>, bool), ApiError>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = Result<Vec<Option<T>>, ApiError>>,
+{
+    let (mut current, mut cursor) = known_cursor(key, page);
+    let mut buffer = Vec::with_capacity(limit as usize);
+    let mut requests = 0;
+
+    loop {
+        if requests == MAX_API_PAGES_PER_CALL {
+            println!(">>> [Backend] Дозаполнение страницы {} прервано после {} запросов", page, requests);
+            if current != page || buffer.is_empty() {
+                // До запрошенной страницы не дошли: пустую страницу не выдаём за незаконченную
+                return Ok((Vec::new(), false));
+            }
+            // Страница короткая, и следующая продолжит с того места, где остановился просмотр
+            remember_cursor(key, page + 1, cursor);
+            return Ok((buffer, true));
+        }
+        requests += 1;
+
+        let items = fetch(cursor.api_page).await?;
+        let exhausted = items.len() < limit as usize;
+
+        let mut items = items.into_iter().enumerate().skip(cursor.skip);
+        while let Some((index, item)) = items.next() {
+            let Some(item) = item else { continue };
+            buffer.push(item);
+            if buffer.len() == limit as usize {
+                let next = Cursor { api_page: cursor.api_page, skip: index + 1 };
+                remember_cursor(key, current + 1, next);
+                if current == page {
+                    // Если на этой странице API подходящих больше нет, но она полная,
+                    // следующая страница, скорее всего, тоже что-то даст
+                    let has_next = items.any(|(_, item)| item.is_some()) || !exhausted;
+                    return Ok((buffer, has_next));
+                }
+                buffer.clear();
+                current += 1;
+            }
+        }
+
+        if exhausted {
+            return Ok(if current == page { (buffer, false) } else { (Vec::new(), false) });
+        }
+        cursor = Cursor { api_page: cursor.api_page + 1, skip: 0 };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Полные страницы API из двух элементов; подходят только элементы с указанных страниц.
+    fn fetch_matching(pages: &'static [u32], last_page: u32, log: &Mutex<Vec<u32>>) -> impl FnMut(u32) -> std::future::Ready<Result<Vec<Option<u32>>, ApiError>> + '_ {
+        move |api_page| {
+            log.lock().unwrap().push(api_page);
+            let items = if api_page > last_page {
+                Vec::new()
+            } else {
+                vec![pages.contains(&api_page).then_some(api_page), None]
+            };
+            std::future::ready(Ok(items))
+        }
+    }
+
+    #[test]
+    fn capped_page_is_short_and_next_page_resumes_after_scan() {
+        let log = Mutex::new(Vec::new());
+        let first = tauri::async_runtime::block_on(filtered_page("refill-cap-resume", 1, 2, fetch_matching(&[1, 12], 13, &log))).unwrap();
+        assert_eq!(first, (vec![1], true));
+        assert_eq!(log.lock().unwrap().len(), MAX_API_PAGES_PER_CALL as usize);
+
+        log.lock().unwrap().clear();
+        let second = tauri::async_runtime::block_on(filtered_page("refill-cap-resume", 2, 2, fetch_matching(&[1, 12], 13, &log))).unwrap();
+        assert_eq!(second, (vec![12], false));
+        assert_eq!(log.lock().unwrap()[0], MAX_API_PAGES_PER_CALL + 1);
+    }
+
+    #[test]
+    fn capped_page_without_matches_has_no_next() {
+        let log = Mutex::new(Vec::new());
+        let page = tauri::async_runtime::block_on(filtered_page("refill-cap-empty", 1, 2, fetch_matching(&[20], 30, &log))).unwrap();
+        assert_eq!(page, (Vec::new(), false));
+    }
+
+    #[test]
+    fn full_pages_record_cursors_for_following_pages() {
+        let log = Mutex::new(Vec::new());
+        let fetch = |api_page: u32| {
+            log.lock().unwrap().push(api_page);
+            let items = if api_page <= 3 { vec![Some(api_page * 10), Some(api_page * 10 + 1)] } else { Vec::new() };
+            std::future::ready(Ok::<_, ApiError>(items))
+        };
+        let second = tauri::async_runtime::block_on(filtered_page("refill-cursors", 2, 2, fetch)).unwrap();
+        assert_eq!(second, (vec![20, 21], true));
+        assert_eq!(known_cursor("refill-cursors", 3).1.api_page, 2);
+    }
+}
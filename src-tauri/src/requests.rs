@@ -0,0 +1,185 @@
+//! Отменяемые команды. Фронтенд передаёт `request_token`, а `cancel_request`
+//! прерывает выполнение: future команды сбрасывается вместе с HTTP-запросом
+//! и слотом лимита запросов, команда возвращает ошибку `cancelled`.
+//!
+//! Каждый вызов клиента Shikimori внутри команды идёт через [`send`]: лимит
+//! запросов действует на отдельные HTTP-запросы, а не на команду целиком.
+
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use shikicrate::ShikicrateError;
+use tokio::sync::Notify;
+
+use crate::rate_limit::{self, Priority};
+use crate::{single_flight, ApiError, ErrorCode};
+
+/// Сколько отмен для ещё не начавшихся запросов помнить.
+const MAX_EARLY_CANCELS: usize = 100;
+
+#[derive(Default)]
+struct Registry {
+    active: HashMap<String, Arc<Notify>>,
+    // Отмена может прийти раньше, чем начнётся сама команда
+    cancelled_early: HashSet<String>,
+}
+
+static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+
+fn registry() -> &'static Mutex<Registry> {
+    REGISTRY.get_or_init(|| Mutex::new(Registry::default()))
+}
+
+/// Выполняет команду; с токеном её можно отменить.
+pub async fn run<T, F>(token: Option<String>, command: F) -> Result<T, ApiError>
+where
+    F: Future<Output = Result<T, ApiError>>,
+{
+    cancellable(token, command).await
+}
+
+/// То же, что [`run`], но одновременные вызовы с одним ключом выполняются одним
+/// запросом. Отмена одного из ожидающих вызовов не влияет на остальные.
+pub async fn run_shared<T, F>(token: Option<String>, key: String, command: F) -> Result<T, ApiError>
+where
+    T: Clone + Send + 'static,
+    F: Future<Output = Result<T, ApiError>>,
+{
+    cancellable(token, single_flight::coalesce(key, command)).await
+}
+
+/// Один запрос клиента Shikimori в слоте лимита запросов пользователя.
+pub async fn send<T, F>(request: F) -> Result<T, ApiError>
+where
+    F: Future<Output = Result<T, ShikicrateError>>,
+{
+    send_with(Priority::Foreground, request).await
+}
+
+/// Один запрос клиента Shikimori в слоте лимита с заданным приоритетом.
+pub async fn send_with<T, F>(priority: Priority, request: F) -> Result<T, ApiError>
+where
+    F: Future<Output = Result<T, ShikicrateError>>,
+{
+    let _slot = rate_limit::acquire_with(priority).await;
+    let result = request.await.map_err(ApiError::from);
+    note_rate_limit(&result);
+    result
+}
+
+/// Приостанавливает фоновые запросы, если API ответил `rate_limit`.
+fn note_rate_limit<T>(result: &Result<T, ApiError>) {
+    if let Err(e) = result {
+        if e.code == ErrorCode::RateLimited {
+            rate_limit::pause(e.retry_after);
+        }
+    }
+}
+
+async fn cancellable<T, F>(token: Option<String>, command: F) -> Result<T, ApiError>
+where
+    F: Future<Output = Result<T, ApiError>>,
+{
+    let Some(token) = token else {
+        return command.await;
+    };
+
+    let cancel = {
+        let mut registry = registry().lock().unwrap();
+        if registry.cancelled_early.remove(&token) {
+            return Err(ApiError::cancelled());
+        }
+        let cancel = Arc::new(Notify::new());
+        registry.active.insert(token.clone(), cancel.clone());
+        cancel
+    };
+
+    let result = tokio::select! {
+        result = command => result,
+        _ = cancel.notified() => {
+            println!("[Backend] Запрос {} отменён", token);
+            Err(ApiError::cancelled())
+        }
+    };
+
+    registry().lock().unwrap().active.remove(&token);
+    result
+}
+
+/// Отменяет команду с этим токеном. Возвращает `false`, если она ещё не началась.
+pub fn cancel(token: &str) -> bool {
+    let mut registry = registry().lock().unwrap();
+    match registry.active.get(token) {
+        Some(cancel) => {
+            cancel.notify_one();
+            true
+        }
+        None => {
+            if registry.cancelled_early.len() >= MAX_EARLY_CANCELS {
+                registry.cancelled_early.clear();
+            }
+            registry.cancelled_early.insert(token.to_string());
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_interrupts_running_command() {
+        let result = tauri::async_runtime::block_on(async {
+            let mut command = Box::pin(run(Some("requests-running".to_string()), std::future::pending::<Result<u32, ApiError>>()));
+            tokio::select! {
+                biased;
+                _ = &mut command => unreachable!(),
+                _ = std::future::ready(()) => {}
+            }
+            assert!(cancel("requests-running"));
+            command.await
+        });
+
+        assert_eq!(result.unwrap_err().code, ErrorCode::Cancelled);
+        assert!(!registry().lock().unwrap().active.contains_key("requests-running"));
+    }
+
+    #[test]
+    fn cancel_before_start_is_remembered_once() {
+        assert!(!cancel("requests-early"));
+
+        let first = tauri::async_runtime::block_on(run(Some("requests-early".to_string()), async { Ok(1) }));
+        let second = tauri::async_runtime::block_on(run(Some("requests-early".to_string()), async { Ok(2) }));
+
+        assert_eq!(first.unwrap_err().code, ErrorCode::Cancelled);
+        assert_eq!(second.unwrap(), 2);
+    }
+
+    #[test]
+    fn cancelled_waiter_does_not_cancel_shared_request() {
+        let (cancelled, other) = tauri::async_runtime::block_on(async {
+            let gate = Notify::new();
+            let command = || async {
+                gate.notified().await;
+                Ok::<_, ApiError>(5)
+            };
+            let mut first = Box::pin(run_shared(None, "requests-shared".to_string(), command()));
+            let mut second = Box::pin(run_shared(Some("requests-shared-waiter".to_string()), "requests-shared".to_string(), command()));
+            tokio::select! {
+                biased;
+                _ = &mut first => unreachable!(),
+                _ = &mut second => unreachable!(),
+                _ = std::future::ready(()) => {}
+            }
+            cancel("requests-shared-waiter");
+            let cancelled = second.await;
+            gate.notify_one();
+            (cancelled, first.await)
+        });
+
+        assert_eq!(cancelled.unwrap_err().code, ErrorCode::Cancelled);
+        assert_eq!(other.unwrap(), 5);
+    }
+}
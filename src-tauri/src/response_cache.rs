@@ -0,0 +1,59 @@
+//! Кэш ответов детальных команд. Его заполняет в том числе предзагрузка,
+//! чтобы карточка открывалась без ожидания сети.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+const TTL: Duration = Duration::from_secs(10 * 60);
+const MAX_ENTRIES: usize = 200;
+
+struct Entry {
+    stored_at: Instant,
+    value: Box<dyn Any + Send>,
+}
+
+static CACHE: OnceLock<Mutex<HashMap<String, Entry>>> = OnceLock::new();
+
+fn cache() -> &'static Mutex<HashMap<String, Entry>> {
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Ключ детальной команды; он же ключ объединения одновременных запросов.
+pub fn detail_key(entity: &str, id: i64, collapse_spoilers: Option<bool>) -> String {
+    format!("{}:{}:{:?}", entity, id, collapse_spoilers)
+}
+
+pub fn get<T: Clone + 'static>(key: &str) -> Option<T> {
+    let mut cache = cache().lock().unwrap();
+    let fresh = cache.get(key)?.stored_at.elapsed() < TTL;
+    if !fresh {
+        cache.remove(key);
+        return None;
+    }
+    cache.get(key)?.value.downcast_ref::<T>().cloned()
+}
+
+pub fn contains(key: &str) -> bool {
+    cache().lock().unwrap().get(key).is_some_and(|e| e.stored_at.elapsed() < TTL)
+}
+
+pub fn insert<T: Send + 'static>(key: String, value: T) {
+    let mut cache = cache().lock().unwrap();
+    if cache.len() >= MAX_ENTRIES && !cache.contains_key(&key) {
+        cache.retain(|_, e| e.stored_at.elapsed() < TTL);
+        if cache.len() >= MAX_ENTRIES {
+            let oldest = cache.iter().min_by_key(|(_, e)| e.stored_at).map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                cache.remove(&oldest);
+            }
+        }
+    }
+    cache.insert(key, Entry { stored_at: Instant::now(), value: Box::new(value) });
+}
+
+/// Сбрасывает кэш, когда настройки меняют содержимое ответов.
+pub fn clear() {
+    cache().lock().unwrap().clear();
+}
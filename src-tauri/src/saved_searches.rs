@@ -0,0 +1,221 @@
+//! Сохранённые поиски: параметры фильтров хранятся целиком, фоновая задача
+//! периодически повторяет поиск и сообщает о тайтлах, которых не было в прошлой выдаче.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use tokio::time;
+
+use crate::rate_limit::Priority;
+use crate::{storage, AnimeSearch, ApiError, MangaSearch};
+
+pub const SAVED_SEARCHES_FILE: &str = "saved_searches.json";
+pub const UPDATE_EVENT: &str = "saved-search-updated";
+
+/// Сколько результатов сравнивается при каждой проверке.
+const RESULT_LIMIT: u32 = 50;
+/// Сколько уже виденных ID помнить на один поиск.
+const MAX_KNOWN_IDS: usize = 1000;
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(60);
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SavedSearchParams {
+    Anime(AnimeSearch),
+    Manga(MangaSearch),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub id: u64,
+    pub name: String,
+    pub params: SavedSearchParams,
+    #[serde(default)]
+    pub known_ids: Vec<i64>,
+    pub created_at: u64,
+    pub last_checked: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct SavedSearchesFile {
+    next_id: u64,
+    searches: Vec<SavedSearch>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedSearchMatch {
+    pub id: i64,
+    pub title: String,
+    pub russian: Option<String>,
+    pub poster_url: Option<String>,
+}
+
+/// Содержимое события [`UPDATE_EVENT`].
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedSearchUpdate {
+    pub search_id: u64,
+    pub name: String,
+    pub new_items: Vec<SavedSearchMatch>,
+}
+
+/// Выполняет поиск с сохранёнными параметрами и возвращает первую страницу выдачи.
+pub async fn execute(app_handle: &tauri::AppHandle, params: &SavedSearchParams) -> Result<Vec<SavedSearchMatch>, ApiError> {
+    let (page, limit) = (Some(1), Some(RESULT_LIMIT));
+    match params.clone() {
+        SavedSearchParams::Anime(search) => {
+            let result = crate::run_anime_search(app_handle.clone(), AnimeSearch { page, limit, ..search }, Priority::Background).await?;
+            Ok(result
+                .items
+                .into_iter()
+                .map(|a| SavedSearchMatch { id: a.id, title: a.title, russian: a.russian, poster_url: a.poster_url })
+                .collect())
+        }
+        SavedSearchParams::Manga(search) => {
+            let result = crate::run_manga_search(app_handle.clone(), MangaSearch { page, limit, ..search }, Priority::Background).await?;
+            Ok(result
+                .items
+                .into_iter()
+                .map(|m| SavedSearchMatch { id: m.id, title: m.title, russian: m.russian, poster_url: m.poster_url })
+                .collect())
+        }
+    }
+}
+
+/// Сохраняет поиск; текущая выдача становится точкой отсчёта, чтобы первая
+/// проверка не объявила новыми все найденные тайтлы.
+pub fn create(path: PathBuf, name: String, params: SavedSearchParams, known_ids: Vec<i64>) -> Result<SavedSearch, String> {
+    let _guard = FILE_LOCK.lock().unwrap();
+    let mut file: SavedSearchesFile = storage::load(&path);
+    let now = storage::now();
+    file.next_id += 1;
+    let search = SavedSearch { id: file.next_id, name, params, known_ids, created_at: now, last_checked: Some(now) };
+    file.searches.push(search.clone());
+    storage::save(&path, &file)?;
+    Ok(search)
+}
+
+pub fn list(path: PathBuf) -> Vec<SavedSearch> {
+    let _guard = FILE_LOCK.lock().unwrap();
+    storage::load::<SavedSearchesFile>(&path).searches
+}
+
+pub fn remove(path: PathBuf, id: u64) -> Result<(), String> {
+    let _guard = FILE_LOCK.lock().unwrap();
+    let mut file: SavedSearchesFile = storage::load(&path);
+    file.searches.retain(|s| s.id != id);
+    storage::save(&path, &file)
+}
+
+// Файл перечитывается после выполнения поиска: пока шёл запрос, поиск могли удалить
+fn mark_checked(path: PathBuf, id: u64, new_ids: &[i64]) -> Result<(), String> {
+    let _guard = FILE_LOCK.lock().unwrap();
+    let mut file: SavedSearchesFile = storage::load(&path);
+    let Some(search) = file.searches.iter_mut().find(|s| s.id == id) else { return Ok(()) };
+    search.known_ids.extend_from_slice(new_ids);
+    let overflow = search.known_ids.len().saturating_sub(MAX_KNOWN_IDS);
+    search.known_ids.drain(..overflow);
+    search.last_checked = Some(storage::now());
+    storage::save(&path, &file)
+}
+
+/// Повторяет все сохранённые поиски и отправляет событие по каждому, где появились новые тайтлы.
+pub async fn check_all(app_handle: &tauri::AppHandle) {
+    let path = match crate::get_saved_searches_path(app_handle) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("Error getting saved searches path: {}", e);
+            return;
+        }
+    };
+
+    for search in list(path.clone()) {
+        let items = match execute(app_handle, &search.params).await {
+            Ok(items) => items,
+            Err(e) => {
+                println!("[Backend] Сохранённый поиск '{}' не выполнен: {}", search.name, e);
+                continue;
+            }
+        };
+
+        let new_items: Vec<SavedSearchMatch> = items.into_iter().filter(|i| !search.known_ids.contains(&i.id)).collect();
+        let new_ids: Vec<i64> = new_items.iter().map(|i| i.id).collect();
+        if let Err(e) = mark_checked(path.clone(), search.id, &new_ids) {
+            eprintln!("Error saving saved search state: {}", e);
+        }
+
+        if !new_items.is_empty() {
+            println!("[Backend] Сохранённый поиск '{}': новых тайтлов {}", search.name, new_items.len());
+            let update = SavedSearchUpdate { search_id: search.id, name: search.name, new_items };
+            let _ = app_handle.emit(UPDATE_EVENT, update);
+        }
+    }
+}
+
+/// Запускает периодическую проверку сохранённых поисков в фоновой задаче.
+pub fn start_scheduler(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut checks = time::interval_at(time::Instant::now() + FIRST_CHECK_DELAY, CHECK_INTERVAL);
+        // После сна системы проверяем один раз, а не за все пропущенные интервалы
+        checks.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+        loop {
+            checks.tick().await;
+            check_all(&app_handle).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("shikimore-{}-{}.json", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn anime_params(query: &str) -> SavedSearchParams {
+        SavedSearchParams::Anime(AnimeSearch { query: query.to_string(), ..Default::default() })
+    }
+
+    #[test]
+    fn known_ids_keep_only_the_newest() {
+        let path = temp_file("saved-known-ids");
+        let search = create(path.clone(), "Naruto".to_string(), anime_params("naruto"), (0..MAX_KNOWN_IDS as i64).collect()).unwrap();
+
+        mark_checked(path.clone(), search.id, &[-1, -2]).unwrap();
+
+        let known = &list(path.clone())[0].known_ids;
+        assert_eq!(known.len(), MAX_KNOWN_IDS);
+        assert_eq!(known[0], 2);
+        assert_eq!(&known[known.len() - 2..], &[-1, -2]);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn removed_search_is_not_recreated_by_check() {
+        let path = temp_file("saved-removed");
+        let search = create(path.clone(), "One Piece".to_string(), anime_params("one piece"), Vec::new()).unwrap();
+        remove(path.clone(), search.id).unwrap();
+
+        mark_checked(path.clone(), search.id, &[1]).unwrap();
+
+        assert!(list(path.clone()).is_empty());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn params_are_stored_with_entity_tag() {
+        let json = serde_json::to_value(anime_params("bleach")).unwrap();
+        assert_eq!(json["type"], "anime");
+        assert_eq!(json["query"], "bleach");
+        let back: SavedSearchParams = serde_json::from_value(json).unwrap();
+        assert!(matches!(back, SavedSearchParams::Anime(search) if search.query == "bleach"));
+    }
+}
@@ -0,0 +1,168 @@
+//! Поиск сразу по всем сущностям: результаты отдельных запросов ранжируются
+//! по совпадению с запросом и сливаются в один список.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use serde::Serialize;
+use tauri::ipc::Channel;
+
+use crate::fuzzy;
+use crate::{Anime, ApiError, Character, Manga, Person, Publisher, Studio};
+
+/// Вес позиции в выдаче API: первые результаты Shikimori обычно самые релевантные.
+const POSITION_WEIGHT: f64 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchEntity {
+    Anime,
+    Manga,
+    Character,
+    Person,
+    Studio,
+    Publisher,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "entity", content = "item", rename_all = "snake_case")]
+pub enum SearchAllItem {
+    Anime(Anime),
+    Manga(Manga),
+    Character(Character),
+    Person(Person),
+    Studio(Studio),
+    Publisher(Publisher),
+}
+
+impl SearchAllItem {
+    fn names(&self) -> Vec<&str> {
+        let (name, russian) = match self {
+            SearchAllItem::Anime(a) => (&a.title, &a.russian),
+            SearchAllItem::Manga(m) => (&m.title, &m.russian),
+            SearchAllItem::Character(c) => (&c.name, &c.russian),
+            SearchAllItem::Person(p) => (&p.name, &p.russian),
+            SearchAllItem::Studio(s) => return vec![s.name.as_str()],
+            SearchAllItem::Publisher(p) => return vec![p.name.as_str()],
+        };
+        std::iter::once(name.as_str()).chain(russian.as_deref()).collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchAllHit {
+    pub rank: f64,
+    #[serde(flatten)]
+    pub item: SearchAllItem,
+}
+
+/// Запрос по одной сущности; у всех запросов общий тип, чтобы их можно было
+/// запускать одной функцией.
+pub type SearchFuture = Pin<Box<dyn Future<Output = Result<Vec<SearchAllItem>, ApiError>> + Send>>;
+
+/// Событие, которое отправляется в канал по мере завершения отдельных запросов.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum SearchAllEvent {
+    Partial { entity: SearchEntity, hits: Vec<SearchAllHit> },
+    Failed { entity: SearchEntity, error: ApiError },
+}
+
+/// Ранжирует результаты одного запроса; `items` идут в порядке выдачи API.
+pub fn rank(query: &str, items: Vec<SearchAllItem>) -> Vec<SearchAllHit> {
+    let total = items.len().max(1) as f64;
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(position, item)| {
+            let text = fuzzy::score(query, item.names());
+            let rank = text + POSITION_WEIGHT * (1.0 - position as f64 / total);
+            SearchAllHit { rank, item }
+        })
+        .collect()
+}
+
+/// Выполняет запрос по одной сущности и сразу отправляет его результат в канал.
+pub async fn run(
+    entity: SearchEntity,
+    query: String,
+    channel: Channel<SearchAllEvent>,
+    search: SearchFuture,
+) -> Result<Vec<SearchAllHit>, ApiError> {
+    match search.await {
+        Ok(items) => {
+            let hits = rank(&query, items);
+            let _ = channel.send(SearchAllEvent::Partial { entity, hits: hits.clone() });
+            Ok(hits)
+        }
+        Err(error) => {
+            println!(">>> [Backend] search_all: запрос {:?} завершился ошибкой: {}", entity, error);
+            let _ = channel.send(SearchAllEvent::Failed { entity, error: error.clone() });
+            Err(error)
+        }
+    }
+}
+
+/// Сливает результаты всех запросов в один список по убыванию ранга.
+/// Ошибка возвращается, только если не удался ни один запрос.
+pub fn merge(results: Vec<Result<Vec<SearchAllHit>, ApiError>>) -> Result<Vec<SearchAllHit>, ApiError> {
+    let mut hits = Vec::new();
+    let mut last_error = None;
+    let mut succeeded = false;
+    for result in results {
+        match result {
+            Ok(mut part) => {
+                succeeded = true;
+                hits.append(&mut part);
+            }
+            Err(error) => last_error = Some(error),
+        }
+    }
+    if let (false, Some(error)) = (succeeded, last_error) {
+        return Err(error);
+    }
+    hits.sort_by(|a, b| b.rank.total_cmp(&a.rank));
+    Ok(hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn studio(id: i64, name: &str) -> SearchAllItem {
+        SearchAllItem::Studio(Studio { id, name: name.to_string(), image_url: None })
+    }
+
+    fn ids(hits: &[SearchAllHit]) -> Vec<i64> {
+        hits.iter()
+            .map(|h| match &h.item {
+                SearchAllItem::Studio(s) => s.id,
+                SearchAllItem::Publisher(p) => p.id,
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn exact_match_outranks_api_position() {
+        let hits = rank("bones", vec![studio(1, "Bonus Studio"), studio(2, "Bones")]);
+        assert!(hits[1].rank > hits[0].rank);
+    }
+
+    #[test]
+    fn merge_sorts_all_parts_by_rank() {
+        let studios = rank("madhouse", vec![studio(1, "Madhouse"), studio(2, "Mad Box")]);
+        let publishers = rank("madhouse", vec![SearchAllItem::Publisher(Publisher { id: 3, name: "Madhouse Books".to_string() })]);
+        let merged = merge(vec![Ok(publishers), Ok(studios)]).unwrap();
+        assert_eq!(ids(&merged), vec![1, 3, 2]);
+    }
+
+    #[test]
+    fn merge_fails_only_when_every_part_failed() {
+        let error = ApiError::validation("boom".to_string());
+        let partial = merge(vec![Err(error.clone()), Ok(rank("a", vec![studio(1, "A")]))]).unwrap();
+        assert_eq!(ids(&partial), vec![1]);
+        assert!(merge(vec![Err(error.clone()), Err(error)]).is_err());
+        assert!(merge(Vec::new()).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,419 @@
+//! Настройки приложения в `config.json`.
+//!
+//! Файл хранит номер версии формата. Старые файлы при чтении проходят через
+//! миграции до текущей версии и сразу перезаписываются. Поле, которое не
+//! удалось разобрать, получает значение по умолчанию, остальные сохраняются;
+//! прежний файл в этом случае остаётся рядом как резервная копия.
+//!
+//! После запуска настройки живут в [`SettingsStore`]: команды читают их из
+//! памяти, а запись проверяет новые значения, атомарно обновляет файл и
+//! только потом подменяет копию в памяти.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::i18n::{t, tf, UiLanguage};
+use crate::spoilers::SpoilerMode;
+use crate::storage;
+use crate::titles::PreferredLanguage;
+use crate::ApiError;
+
+/// Событие с новыми настройками после каждого сохранения.
+pub const CHANGED_EVENT: &str = "settings-changed";
+
+/// Версия формата, которую пишет эта сборка.
+pub const CURRENT_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    System,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ViewMode {
+    #[default]
+    Grid,
+    List,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppSettings {
+    #[serde(default = "current_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub theme: Theme,
+    #[serde(default)]
+    pub nsfw: bool,
+    #[serde(default = "default_accent_color")]
+    pub accent_color: String,
+    /// По нему бэкенд выбирает `display_title` записей.
+    #[serde(default)]
+    pub preferred_language: PreferredLanguage,
+    #[serde(default)]
+    pub view_mode: ViewMode,
+    #[serde(default)]
+    pub autostart: bool,
+    #[serde(default)]
+    pub tray: bool,
+    #[serde(default)]
+    pub spoiler_mode: SpoilerMode,
+    #[serde(default = "default_true")]
+    pub search_history_enabled: bool,
+    /// Язык сообщений бэкенда: ошибок, меню трея.
+    #[serde(default)]
+    pub ui_language: UiLanguage,
+}
+
+fn current_version() -> u32 {
+    CURRENT_VERSION
+}
+
+fn default_accent_color() -> String {
+    "#646cff".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            version: CURRENT_VERSION,
+            theme: Theme::default(),
+            nsfw: false,
+            accent_color: default_accent_color(),
+            preferred_language: PreferredLanguage::default(),
+            view_mode: ViewMode::default(),
+            autostart: false,
+            tray: false,
+            spoiler_mode: SpoilerMode::default(),
+            search_history_enabled: true,
+            ui_language: UiLanguage::default(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// Проверка перед сохранением: значения, которые нельзя выразить типом поля.
+    pub fn validate(&self) -> Result<(), ApiError> {
+        let hex = self.accent_color.strip_prefix('#').unwrap_or_default();
+        if !matches!(hex.len(), 3 | 6) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ApiError::validation(tf(
+                "validation.parameter",
+                &[("param", &"accent_color"), ("reason", &t("validation.hex_color"))],
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Миграция `i` переводит файл из версии `i + 1` в версию `i + 2`.
+const MIGRATIONS: [fn(&mut Map<String, Value>); (CURRENT_VERSION - 1) as usize] = [migrate_v1_to_v2];
+
+/// Версия 1 (без поля `version`) хранила перечисления свободными строками.
+fn migrate_v1_to_v2(fields: &mut Map<String, Value>) {
+    for key in ["theme", "view_mode", "preferred_language", "spoiler_mode", "ui_language"] {
+        if let Some(Value::String(value)) = fields.get_mut(key) {
+            *value = value.trim().to_lowercase();
+        }
+    }
+}
+
+/// Доводит поля до текущей версии формата; `true`, если файл был старее.
+fn migrate(fields: &mut Map<String, Value>) -> bool {
+    let version = fields.get("version").and_then(Value::as_u64).map_or(1, |v| v as u32);
+    for migration in MIGRATIONS.iter().skip(version.saturating_sub(1) as usize) {
+        migration(fields);
+    }
+    fields.insert("version".to_string(), CURRENT_VERSION.into());
+    version < CURRENT_VERSION
+}
+
+/// Итог чтения файла настроек.
+struct Loaded {
+    settings: AppSettings,
+    /// Формат устарел, файл нужно перезаписать.
+    migrated: bool,
+    /// Часть данных не удалось разобрать.
+    damaged: bool,
+}
+
+fn parse(content: &str) -> Loaded {
+    let Ok(Value::Object(mut fields)) = serde_json::from_str::<Value>(content) else {
+        return Loaded { settings: AppSettings::default(), migrated: false, damaged: true };
+    };
+
+    let migrated = migrate(&mut fields);
+    if let Ok(settings) = serde_json::from_value(Value::Object(fields.clone())) {
+        return Loaded { settings, migrated, damaged: false };
+    }
+
+    // Переносим поля по одному, пропуская те, что не разбираются
+    let mut merged = match serde_json::to_value(AppSettings::default()) {
+        Ok(Value::Object(defaults)) => defaults,
+        _ => Map::new(),
+    };
+    for (key, value) in fields {
+        let previous = merged.insert(key.clone(), value);
+        if serde_json::from_value::<AppSettings>(Value::Object(merged.clone())).is_err() {
+            match previous {
+                Some(previous) => merged.insert(key, previous),
+                None => merged.remove(&key),
+            };
+        }
+    }
+    let settings = serde_json::from_value(Value::Object(merged)).unwrap_or_default();
+    Loaded { settings, migrated, damaged: true }
+}
+
+/// Резервная копия рядом с файлом: `config.json.bak-<время>`.
+fn backup(path: &Path) -> Result<PathBuf, String> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".bak-{}", storage::now()));
+    let backup = path.with_file_name(name);
+    fs::copy(path, &backup).map_err(|e| format!("Failed to back up {}: {}", path.display(), e))?;
+    Ok(backup)
+}
+
+/// Читает настройки. Отсутствующий файл даёт настройки по умолчанию,
+/// повреждённый копируется в резервный и заменяется восстановленным.
+pub fn load(path: &Path) -> AppSettings {
+    let Ok(content) = fs::read_to_string(path) else {
+        return AppSettings::default();
+    };
+
+    let Loaded { settings, migrated, damaged } = parse(&content);
+    if damaged {
+        match backup(path) {
+            Ok(backup) => eprintln!("Config {} is damaged, copy saved to {}", path.display(), backup.display()),
+            Err(e) => {
+                // Без копии не перезаписываем файл, чтобы ничего не потерять
+                eprintln!("{}", e);
+                return settings;
+            }
+        }
+    }
+    if migrated || damaged {
+        if let Err(e) = save(path, &settings) {
+            eprintln!("{}", e);
+        }
+    }
+    settings
+}
+
+pub fn save(path: &Path, settings: &AppSettings) -> Result<(), String> {
+    let settings = AppSettings { version: CURRENT_VERSION, ..settings.clone() };
+    storage::save(path, &settings)
+}
+
+/// Метка переносимого файла настроек (экспорт, профили).
+const PORTABLE_FORMAT: &str = "shikimore-settings";
+
+/// Переносимый файл: настройки с меткой формата, чтобы не принять за них
+/// случайный JSON.
+#[derive(Debug, Serialize, Deserialize)]
+struct PortableFile {
+    format: String,
+    settings: Value,
+}
+
+pub fn write_portable(path: &Path, settings: &AppSettings) -> Result<(), String> {
+    let settings = AppSettings { version: CURRENT_VERSION, ..settings.clone() };
+    let settings = serde_json::to_value(settings).map_err(|e| e.to_string())?;
+    storage::save(path, &PortableFile { format: PORTABLE_FORMAT.to_string(), settings })
+}
+
+/// Читает переносимый файл. В отличие от `config.json`, ошибка в любом поле
+/// отклоняет файл целиком: чужие настройки не применяются наполовину.
+pub fn read_portable(path: &Path) -> Result<AppSettings, ApiError> {
+    let invalid = || ApiError::validation(t("validation.settings_file").to_string());
+    let content = fs::read_to_string(path).map_err(ApiError::storage)?;
+    let file: PortableFile = serde_json::from_str(&content).map_err(|e| invalid().caused_by(e))?;
+    let Value::Object(mut fields) = file.settings else {
+        return Err(invalid());
+    };
+    if file.format != PORTABLE_FORMAT {
+        return Err(invalid().caused_by(format!("unknown format \"{}\"", file.format)));
+    }
+    migrate(&mut fields);
+    let settings: AppSettings = serde_json::from_value(Value::Object(fields)).map_err(|e| invalid().caused_by(e))?;
+    settings.validate()?;
+    Ok(settings)
+}
+
+/// Прежние и новые настройки после сохранения.
+pub struct Change {
+    pub previous: AppSettings,
+    pub current: AppSettings,
+}
+
+/// Настройки в памяти; файл читается один раз, при создании.
+pub struct SettingsStore {
+    path: PathBuf,
+    current: RwLock<AppSettings>,
+    // Сохранения идут по очереди, чтобы файл и память не разошлись
+    writing: Mutex<()>,
+}
+
+impl SettingsStore {
+    pub fn open(path: PathBuf) -> Self {
+        let current = RwLock::new(load(&path));
+        SettingsStore { path, current, writing: Mutex::new(()) }
+    }
+
+    pub fn get(&self) -> AppSettings {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Проверяет и сохраняет настройки. При ошибке в памяти остаются прежние.
+    pub fn update(&self, settings: AppSettings) -> Result<Change, ApiError> {
+        settings.validate()?;
+        let settings = AppSettings { version: CURRENT_VERSION, ..settings };
+
+        let _writing = self.writing.lock().unwrap();
+        save(&self.path, &settings).map_err(ApiError::storage)?;
+        let previous = std::mem::replace(&mut *self.current.write().unwrap(), settings.clone());
+        Ok(Change { previous, current: settings })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v1_file_is_migrated() {
+        let loaded = parse(r#"{"theme": " Light ", "view_mode": "LIST", "ui_language": "EN", "nsfw": true}"#);
+        assert!(loaded.migrated && !loaded.damaged);
+        assert_eq!(loaded.settings.version, CURRENT_VERSION);
+        assert_eq!(loaded.settings.theme, Theme::Light);
+        assert_eq!(loaded.settings.view_mode, ViewMode::List);
+        assert_eq!(loaded.settings.ui_language, UiLanguage::En);
+        assert!(loaded.settings.nsfw);
+    }
+
+    #[test]
+    fn corrupt_v1_fields_fall_back_one_by_one() {
+        let loaded = parse(
+            r#"{"theme": "LIGHT", "preferred_language": "klingon", "nsfw": "yes", "accent_color": "#123456", "spoiler_mode": 3}"#,
+        );
+        assert!(loaded.migrated && loaded.damaged);
+        let defaults = AppSettings::default();
+        assert_eq!(loaded.settings.theme, Theme::Light);
+        assert_eq!(loaded.settings.accent_color, "#123456");
+        assert_eq!(loaded.settings.preferred_language, defaults.preferred_language);
+        assert_eq!(loaded.settings.nsfw, defaults.nsfw);
+        assert_eq!(loaded.settings.spoiler_mode, defaults.spoiler_mode);
+        assert_eq!(loaded.settings.version, CURRENT_VERSION);
+    }
+
+    #[test]
+    fn unreadable_file_gives_defaults() {
+        for content in ["", "{\"theme\": ", "[1, 2]", "null"] {
+            let loaded = parse(content);
+            assert!(loaded.damaged && !loaded.migrated, "{:?}", content);
+            assert_eq!(loaded.settings, AppSettings::default());
+        }
+    }
+
+    #[test]
+    fn current_version_is_not_migrated() {
+        let loaded = parse(r#"{"version": 2, "theme": "light"}"#);
+        assert!(!loaded.migrated && !loaded.damaged);
+        assert_eq!(loaded.settings.theme, Theme::Light);
+
+        // Миграция v1 не применяется к файлу v2
+        let loaded = parse(r#"{"version": 2, "theme": "Light"}"#);
+        assert!(!loaded.migrated && loaded.damaged);
+        assert_eq!(loaded.settings.theme, Theme::default());
+    }
+
+    #[test]
+    fn corrupt_file_is_backed_up_and_repaired() {
+        let dir = std::env::temp_dir().join(format!("shikimore-settings-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        fs::write(&path, r#"{"theme": "Light", "nsfw": "yes"}"#).unwrap();
+
+        let settings = load(&path);
+        assert_eq!(settings.theme, Theme::Light);
+        assert!(!settings.nsfw);
+
+        let backups: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().starts_with("config.json.bak-"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        let repaired = parse(&fs::read_to_string(&path).unwrap());
+        assert!(!repaired.migrated && !repaired.damaged);
+        assert_eq!(repaired.settings, settings);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("shikimore-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn store_update_saves_and_reports_previous() {
+        let dir = temp_dir("settings-store");
+        let store = SettingsStore::open(dir.join("config.json"));
+
+        let change = store.update(AppSettings { nsfw: true, ..store.get() }).unwrap();
+
+        assert!(!change.previous.nsfw && change.current.nsfw);
+        assert!(store.get().nsfw);
+        assert!(SettingsStore::open(dir.join("config.json")).get().nsfw);
+        assert!(!dir.join("config.json.tmp").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_update_keeps_file_and_memory() {
+        let dir = temp_dir("settings-invalid");
+        let store = SettingsStore::open(dir.join("config.json"));
+
+        let error = store.update(AppSettings { accent_color: "red".to_string(), ..store.get() }).unwrap_err();
+
+        assert_eq!(error.code, crate::ErrorCode::InvalidParameter);
+        assert_eq!(store.get(), AppSettings::default());
+        assert!(!dir.join("config.json").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn concurrent_updates_leave_file_matching_memory() {
+        let dir = temp_dir("settings-concurrent");
+        let store = std::sync::Arc::new(SettingsStore::open(dir.join("config.json")));
+
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let store = store.clone();
+                std::thread::spawn(move || {
+                    store.update(AppSettings { accent_color: format!("#00000{}", i), ..AppSettings::default() }).unwrap();
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        assert_eq!(load(&dir.join("config.json")), store.get());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,122 @@
+//! Объединение одинаковых одновременных запросов: пока запрос с ключом
+//! выполняется, повторные вызовы не идут в сеть, а ждут его результат.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+
+use tokio::sync::broadcast;
+
+static IN_FLIGHT: OnceLock<Mutex<HashMap<String, Box<dyn Any + Send>>>> = OnceLock::new();
+
+fn in_flight() -> &'static Mutex<HashMap<String, Box<dyn Any + Send>>> {
+    IN_FLIGHT.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// Снимает запрос с учёта, если его future сбросили до результата (отмена),
+// тогда ожидающие вызовы выполнят запрос сами
+struct Leader {
+    key: String,
+    active: bool,
+}
+
+impl Drop for Leader {
+    fn drop(&mut self) {
+        if self.active {
+            in_flight().lock().unwrap().remove(&self.key);
+        }
+    }
+}
+
+/// Выполняет `command` или, если запрос с тем же ключом уже идёт, возвращает
+/// его результат. Ключ должен включать имя команды и все её параметры.
+pub async fn coalesce<T, F>(key: String, command: F) -> T
+where
+    T: Clone + Send + 'static,
+    F: Future<Output = T>,
+{
+    loop {
+        let mut receiver = {
+            let mut in_flight = in_flight().lock().unwrap();
+            match in_flight.get(&key).and_then(|sender| sender.downcast_ref::<broadcast::Sender<T>>()) {
+                Some(sender) => sender.subscribe(),
+                None => {
+                    let (sender, _) = broadcast::channel::<T>(1);
+                    in_flight.insert(key.clone(), Box::new(sender));
+                    break;
+                }
+            }
+        };
+        if let Ok(value) = receiver.recv().await {
+            return value;
+        }
+    }
+
+    let mut leader = Leader { key, active: true };
+    let value = command.await;
+
+    let mut in_flight = in_flight().lock().unwrap();
+    if let Some(sender) = in_flight.remove(&leader.key) {
+        if let Ok(sender) = sender.downcast::<broadcast::Sender<T>>() {
+            let _ = sender.send(value.clone());
+        }
+    }
+    leader.active = false;
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use tokio::sync::Notify;
+
+    /// Опрашивает future один раз, чтобы он встал на ожидание.
+    async fn poll_once<F: Future + Unpin>(future: &mut F) {
+        tokio::select! {
+            biased;
+            _ = future => panic!("future не должен был завершиться"),
+            _ = std::future::ready(()) => {}
+        }
+    }
+
+    #[test]
+    fn concurrent_calls_share_one_execution() {
+        let calls = AtomicU32::new(0);
+        let gate = Notify::new();
+        let command = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            gate.notified().await;
+            42
+        };
+
+        let (first, second, _) = tauri::async_runtime::block_on(async {
+            tokio::join!(
+                coalesce("single-flight-shared".to_string(), command()),
+                coalesce("single-flight-shared".to_string(), command()),
+                async { gate.notify_one() },
+            )
+        });
+
+        assert_eq!((first, second), (42, 42));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn follower_runs_itself_when_leader_is_dropped() {
+        let key = "single-flight-leader-drop".to_string();
+        let result = tauri::async_runtime::block_on(async {
+            let mut leader = Box::pin(coalesce(key.clone(), std::future::pending::<u32>()));
+            poll_once(&mut leader).await;
+            let mut follower = Box::pin(coalesce(key.clone(), async { 7 }));
+            poll_once(&mut follower).await;
+
+            drop(leader);
+            follower.await
+        });
+
+        assert_eq!(result, 7);
+        assert!(!in_flight().lock().unwrap().contains_key(&key));
+    }
+}
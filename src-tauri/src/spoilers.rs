@@ -0,0 +1,69 @@
+//! Обработка спойлеров в описаниях, списках ролей и связанных тайтлах
+//! согласно настройке `AppSettings.spoiler_mode`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::bbcode::{self, RichNode};
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SpoilerMode {
+    /// Спойлеры раскрыты и показываются как обычный текст.
+    Show,
+    /// Спойлеры остаются размеченными, фронтенд скрывает их до клика.
+    #[default]
+    Blur,
+    /// Спойлеры вырезаются целиком.
+    Strip,
+}
+
+/// Применяет режим к сырому BBCode-описанию.
+pub fn apply_to_bbcode(text: String, mode: SpoilerMode) -> String {
+    match mode {
+        SpoilerMode::Blur => text,
+        SpoilerMode::Show => bbcode::rewrite_spoilers(&text, true),
+        SpoilerMode::Strip => bbcode::rewrite_spoilers(&text, false),
+    }
+}
+
+/// Применяет режим к подписи элемента списка (текст связи, название роли).
+/// `None`, если после вырезания спойлеров подпись пуста.
+pub fn apply_to_label(text: String, mode: SpoilerMode) -> Option<String> {
+    let text = apply_to_bbcode(text, mode);
+    if text.trim().is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+/// Применяет режим к списку подписей, отбрасывая ставшие пустыми.
+pub fn apply_to_labels(labels: Vec<String>, mode: SpoilerMode) -> Vec<String> {
+    labels.into_iter().filter_map(|label| apply_to_label(label, mode)).collect()
+}
+
+/// Применяет режим к разобранному описанию: раскрывает или вырезает узлы `Spoiler`.
+pub fn apply_to_rich(nodes: Vec<RichNode>, mode: SpoilerMode) -> Vec<RichNode> {
+    if mode == SpoilerMode::Blur {
+        return nodes;
+    }
+
+    let mut out = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        match node {
+            RichNode::Spoiler { children, .. } => {
+                if mode == SpoilerMode::Show {
+                    out.extend(apply_to_rich(children, mode));
+                }
+            }
+            RichNode::Format { style, children } => out.push(RichNode::Format { style, children: apply_to_rich(children, mode) }),
+            RichNode::Url { href, children } => out.push(RichNode::Url { href, children: apply_to_rich(children, mode) }),
+            RichNode::Quote { author, children } => out.push(RichNode::Quote { author, children: apply_to_rich(children, mode) }),
+            RichNode::List { items } => out.push(RichNode::List {
+                items: items.into_iter().map(|item| apply_to_rich(item, mode)).collect(),
+            }),
+            other => out.push(other),
+        }
+    }
+    out
+}
@@ -0,0 +1,156 @@
+//! Выбор названия для показа по настройке `preferred_language`. Бэкенд
+//! заполняет `display_title` и `secondary_title` у всех записей, чтобы окно
+//! не выбирало название по-своему в каждой карточке.
+//!
+//! Если нужного варианта нет, берётся следующий по цепочке своего языка;
+//! `title` (название на ромадзи) есть у записи всегда.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreferredLanguage {
+    #[default]
+    Russian,
+    English,
+    Original,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Name {
+    LicenseNameRu,
+    Russian,
+    English,
+    Japanese,
+    Title,
+}
+
+impl PreferredLanguage {
+    /// Порядок вариантов для основного названия.
+    fn display_order(self) -> &'static [Name] {
+        match self {
+            PreferredLanguage::Russian => &[Name::LicenseNameRu, Name::Russian, Name::English, Name::Title, Name::Japanese],
+            PreferredLanguage::English => &[Name::English, Name::Title, Name::LicenseNameRu, Name::Russian, Name::Japanese],
+            PreferredLanguage::Original => &[Name::Title, Name::Japanese, Name::English, Name::LicenseNameRu, Name::Russian],
+        }
+    }
+
+    /// Порядок вариантов для подписи под названием.
+    fn secondary_order(self) -> &'static [Name] {
+        match self {
+            PreferredLanguage::Russian | PreferredLanguage::English => &[Name::Title, Name::Japanese, Name::English],
+            PreferredLanguage::Original => &[Name::LicenseNameRu, Name::Russian, Name::English, Name::Japanese],
+        }
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+pub fn current() -> PreferredLanguage {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => PreferredLanguage::English,
+        2 => PreferredLanguage::Original,
+        _ => PreferredLanguage::Russian,
+    }
+}
+
+pub fn set(language: PreferredLanguage) {
+    CURRENT.store(language as u8, Ordering::Relaxed);
+}
+
+/// Все известные варианты названия записи.
+#[derive(Debug, Default)]
+pub struct Names<'a> {
+    pub title: &'a str,
+    pub russian: Option<&'a str>,
+    pub license_name_ru: Option<&'a str>,
+    pub english: Option<&'a str>,
+    pub japanese: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Titles {
+    #[serde(default)]
+    pub display_title: String,
+    /// Другой вариант названия, если он отличается от основного.
+    #[serde(default)]
+    pub secondary_title: Option<String>,
+}
+
+impl Names<'_> {
+    fn get(&self, name: Name) -> Option<&str> {
+        let value = match name {
+            Name::LicenseNameRu => self.license_name_ru,
+            Name::Russian => self.russian,
+            Name::English => self.english,
+            Name::Japanese => self.japanese,
+            Name::Title => Some(self.title),
+        };
+        value.map(str::trim).filter(|v| !v.is_empty())
+    }
+
+    /// Названия по текущей настройке `preferred_language`.
+    pub fn titles(&self) -> Titles {
+        self.titles_for(current())
+    }
+
+    fn titles_for(&self, language: PreferredLanguage) -> Titles {
+        let display = language
+            .display_order()
+            .iter()
+            .find_map(|n| self.get(*n))
+            .unwrap_or_else(|| crate::i18n::t("common.unknown"));
+        let secondary = language
+            .secondary_order()
+            .iter()
+            .filter_map(|n| self.get(*n))
+            .find(|s| !s.eq_ignore_ascii_case(display));
+        Titles {
+            display_title: display.to_string(),
+            secondary_title: secondary.map(str::to_string),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naruto() -> Names<'static> {
+        Names {
+            title: "Naruto",
+            russian: Some("Наруто"),
+            license_name_ru: None,
+            english: Some("Naruto"),
+            japanese: Some("ナルト"),
+        }
+    }
+
+    #[test]
+    fn display_title_follows_preferred_language() {
+        let russian = naruto().titles_for(PreferredLanguage::Russian);
+        assert_eq!(russian.display_title, "Наруто");
+        assert_eq!(russian.secondary_title.as_deref(), Some("Naruto"));
+
+        let original = naruto().titles_for(PreferredLanguage::Original);
+        assert_eq!(original.display_title, "Naruto");
+        assert_eq!(original.secondary_title.as_deref(), Some("Наруто"));
+    }
+
+    #[test]
+    fn secondary_title_skips_variants_equal_to_display() {
+        let english = naruto().titles_for(PreferredLanguage::English);
+        assert_eq!(english.display_title, "Naruto");
+        assert_eq!(english.secondary_title.as_deref(), Some("ナルト"));
+    }
+
+    #[test]
+    fn blank_variants_are_skipped() {
+        let names = Names { title: "Shingeki no Kyojin", russian: Some("  "), license_name_ru: Some(""), ..Default::default() };
+        let titles = names.titles_for(PreferredLanguage::Russian);
+        assert_eq!(titles.display_title, "Shingeki no Kyojin");
+        assert_eq!(titles.secondary_title, None);
+    }
+}
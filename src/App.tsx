@@ -1,5 +1,6 @@
 import { useState, useEffect, useCallback, useRef } from "react";
 import { open as openUrl } from "@tauri-apps/plugin-shell";
+import { listen } from "@tauri-apps/api/event";
 
 // Components
 import DetailView from "./components/DetailView";
@@ -11,7 +12,7 @@ import { TitleBar } from "./components/TitleBar";
 import { ToastContainer } from "./components/common/Toast";
 
 // Hooks & Services
-import { api } from "./services/api";
+import { api, newRequestToken } from "./services/api";
 import { useAppSettings } from "./hooks/useAppSettings";
 import { useShikimoriApi } from "./hooks/useShikimoriApi";
 import { useSearchHistory } from "./hooks/useSearchHistory";
@@ -25,15 +26,21 @@ import {
   MangaDetail, 
   CharacterDetail,
   Genre,
+  GenreCatalog,
+  SavedSearchUpdate,
   Studio,
   Publisher,
   SortOption,
   Toast,
-  ApiError
+  ApiError,
+  DetailEvent
 } from "./types";
 
 import "./App.css";
 
+/** Сколько первых карточек выдачи предзагружать. */
+const PREFETCH_VISIBLE = 6;
+
 function App() {
   const { settings, updateSettings } = useAppSettings();
   const [showSettings, setShowSettings] = useState(false);
@@ -46,6 +53,7 @@ function App() {
   const [studioFilter, setStudioFilter] = useState<string>("");
   const [studioInput, setStudioInput] = useState<string>("");
   const [genreFilter, setGenreFilter] = useState<string>("");
+  const [genreCatalog, setGenreCatalog] = useState<GenreCatalog | null>(null);
   const [allGenres, setAllGenres] = useState<Genre[]>([]);
   const [studioSuggestions, setStudioSuggestions] = useState<Studio[] | Publisher[]>([]);
   const [isSearchingStudios, setIsSearchingStudios] = useState(false);
@@ -81,7 +89,7 @@ function App() {
     search
   } = useShikimoriApi();
 
-  const { searchHistory, setSearchHistory, addToHistory } = useSearchHistory();
+  const { searchHistory, addToHistory, removeFromHistory, clearHistory } = useSearchHistory();
   
   const handleLoadMore = useCallback(() => {
     if (!loading && !loadingMore && hasMore) {
@@ -116,18 +124,7 @@ function App() {
   useEffect(() => {
     const fetchGenres = async () => {
       try {
-        const genres = await api.getGenres();
-        const genresByName = new Map<string, Genre & { ids: number[] }>();
-        genres.forEach(g => {
-          const key = g.russian || g.name;
-          const existing = genresByName.get(key);
-          if (existing) {
-            if (!existing.ids.includes(g.id)) existing.ids.push(g.id);
-          } else {
-            genresByName.set(key, { ...g, ids: [g.id] } as any);
-          }
-        });
-        setAllGenres(Array.from(genresByName.values()) as any);
+        setGenreCatalog(await api.getGenres());
       } catch (err) {
         console.error("Ошибка загрузки жанров:", err);
       }
@@ -135,6 +132,23 @@ function App() {
     fetchGenres();
   }, []);
 
+  useEffect(() => {
+    if (!genreCatalog) return;
+    const groups = contentType === "manga" ? genreCatalog.manga : genreCatalog.anime;
+    if (!groups) return;
+    const genresByName = new Map<string, Genre & { ids: number[] }>();
+    [...(groups.genres ?? []), ...(groups.themes ?? []), ...(groups.demographics ?? [])].forEach(g => {
+      const key = g.label || g.russian || g.name;
+      const existing = genresByName.get(key);
+      if (existing) {
+        if (!existing.ids.includes(g.id)) existing.ids.push(g.id);
+      } else {
+        genresByName.set(key, { ...g, ids: [g.id] } as any);
+      }
+    });
+    setAllGenres(Array.from(genresByName.values()) as any);
+  }, [genreCatalog, contentType]);
+
   // Toast
   const showToast = useCallback((message: string, type: Toast["type"] = "success") => {
     const id = Math.random().toString(36).substr(2, 9);
@@ -144,6 +158,31 @@ function App() {
     }, 3000);
   }, []);
 
+  // Новые тайтлы в сохранённых поисках
+  useEffect(() => {
+    const unlisten = listen<SavedSearchUpdate>("saved-search-updated", (event) => {
+      const { name, new_items } = event.payload;
+      const titles = new_items.slice(0, 3).map(item => item.russian || item.title).join(", ");
+      const more = new_items.length > 3 ? ` и ещё ${new_items.length - 3}` : "";
+      showToast(`«${name}»: ${titles}${more}`, "info");
+    }).catch(() => undefined);
+    return () => {
+      unlisten.then(fn => fn?.());
+    };
+  }, [showToast]);
+
+  // Фильтры, с которыми запрос попадает в историю
+  const historyFilters = useCallback(() => {
+    const filters: Record<string, string> = {
+      kind: kindFilter,
+      status: statusFilter,
+      genre: genreFilter,
+      studio: studioFilter,
+      order: sortBy === "relevance" ? "" : sortBy,
+    };
+    return Object.fromEntries(Object.entries(filters).filter(([, value]) => value));
+  }, [kindFilter, statusFilter, genreFilter, studioFilter, sortBy]);
+
   // Search logic
   const performSearch = useCallback((q: string = searchQuery) => {
     if (contentType === "characters" || contentType === "people") {
@@ -165,9 +204,9 @@ function App() {
       limit: 20
     });
     
-    if (q.trim()) addToHistory(q);
+    if (q.trim()) addToHistory(q, contentType, historyFilters());
         setShowHistory(false);
-  }, [searchQuery, contentType, kindFilter, statusFilter, genreFilter, studioFilter, sortBy, search, setContentList, setHasMore, addToHistory]);
+  }, [searchQuery, contentType, kindFilter, statusFilter, genreFilter, studioFilter, sortBy, search, setContentList, setHasMore, addToHistory, historyFilters]);
 
   const handleSearchKeyDown = (e: React.KeyboardEvent<HTMLInputElement>) => {
     if (e.key === "Enter") {
@@ -204,7 +243,7 @@ function App() {
       limit: 20
     });
     
-    if (searchQuery.trim()) addToHistory(searchQuery);
+    if (searchQuery.trim()) addToHistory(searchQuery, contentType, { ...historyFilters(), studio: newFilter });
     setShowHistory(false);
   };
 
@@ -216,22 +255,31 @@ function App() {
       return;
     }
 
+    // Запрос для устаревшего ввода отменяем, чтобы он не перезаписал подсказки
+    const token = newRequestToken("studios");
+    let inFlight = false;
     const timer = setTimeout(async () => {
+      inFlight = true;
       setIsSearchingStudios(true);
         try {
         const results = contentType === "anime" 
-          ? await api.searchStudios(studioInput)
-          : await api.searchPublishers(studioInput);
+          ? await api.searchStudios(studioInput, token)
+          : await api.searchPublishers(studioInput, token);
           setStudioSuggestions(results);
           setShowStudioSuggestions(true);
         } catch (err) {
+        if ((err as ApiError).kind === "cancelled") return;
         console.error("Error fetching suggestions:", err);
         } finally {
+          inFlight = false;
           setIsSearchingStudios(false);
         }
     }, 500);
 
-    return () => clearTimeout(timer);
+    return () => {
+      clearTimeout(timer);
+      if (inFlight) api.cancelRequest(token).catch(() => {});
+    };
   }, [studioInput, contentType, studioFilter]);
 
   // Detail loading
@@ -241,24 +289,45 @@ function App() {
       return;
     }
 
+    // Шапка аниме и манги приходит сразу, тяжёлые разделы догружаются следом
+    let stale = false;
+    let done = false;
+    const token = newRequestToken("detail");
+    const onEvent = (event: DetailEvent<AnimeDetail | MangaDetail>) => {
+      if (stale) return;
+      if (event.event === "core") {
+        setDetailData(event.data);
+        setLoadingDetail(false);
+      } else if (event.event === "section") {
+        const { section, data } = event.data;
+        setDetailData(prev => (prev ? { ...prev, [section]: data } as AnimeDetail | MangaDetail : prev));
+      }
+    };
+
     const fetchDetail = async () => {
     setLoadingDetail(true);
     setDetailError(null);
     try {
-        let data;
-        if (selectedItem.type === "anime") data = await api.getAnimeById(selectedItem.id);
-        else if (selectedItem.type === "manga") data = await api.getMangaById(selectedItem.id);
-        else data = await api.getCharacterDetails(selectedItem.id);
-        setDetailData(data);
+        if (selectedItem.type === "anime") await api.streamAnimeDetail(selectedItem.id, onEvent, undefined, token);
+        else if (selectedItem.type === "manga") await api.streamMangaDetail(selectedItem.id, onEvent, undefined, token);
+        else {
+          const data = await api.getCharacterDetails(selectedItem.id, undefined, token);
+          if (!stale) setDetailData(data);
+        }
     } catch (err) {
         const apiErr = err as ApiError;
-        setDetailError(apiErr.message || "Ошибка загрузки деталей");
+        if (!stale && apiErr.kind !== "cancelled") setDetailError(apiErr.message || "Ошибка загрузки деталей");
     } finally {
-      setLoadingDetail(false);
+      done = true;
+      if (!stale) setLoadingDetail(false);
     }
     };
 
     fetchDetail();
+    return () => {
+      stale = true;
+      if (!done) api.cancelRequest(token).catch(() => {});
+    };
   }, [selectedItem]);
 
   const handleContentClick = (item: ContentItem) => {
@@ -267,10 +336,27 @@ function App() {
       openUrl(item.url);
       return;
     }
+    if (!selectedItem && searchQuery.trim()) {
+      const title = "title" in item ? item.title : item.name;
+      addToHistory(searchQuery, contentType, historyFilters(), { id: item.id, title: item.russian || title });
+    }
     if (selectedItem) setNavigationHistory(prev => [...prev, selectedItem]);
     setSelectedItem({ type: type as ContentType, id: item.id });
   };
 
+  // Детали наведённой карточки загружаются заранее, чтобы она открылась без ожидания сети
+  const handleContentHover = (item: ContentItem) => {
+    if (!("title" in item)) return;
+    api.prefetch("episodes" in item ? "anime" : "manga", [item.id], "hover").catch(() => {});
+  };
+
+  // Первые карточки выдачи видны сразу, их детали тоже загружаем заранее
+  useEffect(() => {
+    if (contentType !== "anime" && contentType !== "manga") return;
+    const ids = contentList.slice(0, PREFETCH_VISIBLE).map(item => item.id);
+    if (ids.length > 0) api.prefetch(contentType, ids, "visible").catch(() => {});
+  }, [contentList, contentType]);
+
   const handleBack = () => {
     if (navigationHistory.length > 0) {
       const prev = navigationHistory[navigationHistory.length - 1];
@@ -361,11 +447,13 @@ function App() {
           hasMore={hasMore}
           onRetry={performSearch}
           onItemClick={handleContentClick}
+          onItemHover={handleContentHover}
           settings={settings}
           searchQuery={searchQuery}
           setSearchQuery={setSearchQuery}
           searchHistory={searchHistory}
-          setSearchHistory={setSearchHistory}
+          clearHistory={clearHistory}
+          removeFromHistory={removeFromHistory}
           showHistory={showHistory}
           setShowHistory={setShowHistory}
           isSearching={loading}
@@ -7,7 +7,7 @@ import { LoadingSpinner } from "./common/LoadingSpinner";
 import { MarqueeText } from "./common/MarqueeText";
 import { RelatedCard } from "./RelatedCard";
 import { PersonCard } from "./PersonCard";
-import { translateRole } from "../utils/formatters";
+import { translateRole, parseAppLink } from "../utils/formatters";
 
 interface CharacterDetailViewProps {
   data: CharacterDetail | null;
@@ -56,11 +56,13 @@ export default function CharacterDetailView({
   }
 
   const prefLang = settings?.preferred_language || "russian";
-  const displayTitle = (prefLang === "russian" 
+  const displayTitle = data.display_title || (prefLang === "russian" 
     ? (data.russian || data.name) 
     : (data.name || data.russian)) || "";
   
-  const subTitle = (displayTitle === data.russian ? data.name : data.russian) || "";
+  const subTitle = (data.display_title
+    ? data.secondary_title
+    : (displayTitle === data.russian ? data.name : data.russian)) || "";
 
   return (
     <div className="detail-view">
@@ -120,6 +122,8 @@ export default function CharacterDetailView({
                     const link = target.closest('a');
                     if (link && link.getAttribute('href')) {
                       const href = link.getAttribute('href') || "";
+                      const appLink = parseAppLink(href);
+                      if (appLink) { e.preventDefault(); onNavigate(appLink.type, appLink.id); return; }
                       const charMatch = href.match(/\/characters\/(\d+)/);
                       const animeMatch = href.match(/\/animes\/(\d+)/);
                       const mangaMatch = href.match(/\/mangas\/(\d+)/);
@@ -160,6 +164,7 @@ export default function CharacterDetailView({
                   id={person.id}
                   name={person.name}
                   russian={person.russian}
+                  displayTitle={person.display_title}
                   poster_url={person.poster_url}
                   role="Сейю"
                   onClick={() => onNavigate("people", person.id)}
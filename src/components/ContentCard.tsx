@@ -11,6 +11,7 @@ interface ContentCardProps {
   settings: AppSettings | null;
   cardColor?: string;
   onItemClick: (item: ContentItem) => void;
+  onItemHover?: (item: ContentItem) => void;
   onCopyLink: (e: React.MouseEvent, url: string) => void;
   onImageLoad: (e: React.SyntheticEvent<HTMLImageElement, Event>, id: number) => void;
   innerRef?: (el: HTMLDivElement | null) => void;
@@ -21,6 +22,7 @@ export const ContentCard: React.FC<ContentCardProps> = ({
   settings,
   cardColor,
   onItemClick,
+  onItemHover,
   onCopyLink,
   onImageLoad,
   innerRef,
@@ -29,11 +31,13 @@ export const ContentCard: React.FC<ContentCardProps> = ({
   const russianTitle = "russian" in item ? item.russian : undefined;
   const originalTitle = "title" in item ? item.title : "name" in item ? item.name : "";
 
-  const displayTitle = (prefLang === "russian"
+  const displayTitle = item.display_title || (prefLang === "russian"
     ? (russianTitle || originalTitle)
     : (originalTitle || russianTitle)) || "";
 
-  const subTitle = (displayTitle === russianTitle ? originalTitle : russianTitle) || "";
+  const subTitle = (item.display_title
+    ? item.secondary_title
+    : (displayTitle === russianTitle ? originalTitle : russianTitle)) || "";
 
   const url = "url" in item ? item.url : undefined;
   const posterUrl = "poster_url" in item ? item.poster_url : undefined;
@@ -48,6 +52,7 @@ export const ContentCard: React.FC<ContentCardProps> = ({
       className="anime-item"
       role="listitem"
       onClick={() => onItemClick(item)}
+      onMouseEnter={() => onItemHover?.(item)}
       onKeyDown={(e) => {
         if (e.key === "Enter" || e.key === " ") {
           e.preventDefault();
@@ -21,7 +21,8 @@ import {
   formatStatus, 
   formatKind, 
   formatRating,
-  formatDate
+  formatDate,
+  parseAppLink
 } from "../utils/formatters";
 
 interface DetailViewProps {
@@ -96,11 +97,13 @@ export default function DetailView({
   const mangaData = !isAnime ? (data as MangaDetail) : null;
 
   const prefLang = settings?.preferred_language || "russian";
-  const displayTitle = (prefLang === "russian" 
+  const displayTitle = data.display_title || (prefLang === "russian" 
     ? (data.russian || (data as any).title || (data as any).name) 
     : ((data as any).title || (data as any).name || data.russian)) || "";
   
-  const subTitle = (displayTitle === data.russian ? ((data as any).title || (data as any).name) : data.russian) || "";
+  const subTitle = (data.display_title
+    ? data.secondary_title
+    : (displayTitle === data.russian ? ((data as any).title || (data as any).name) : data.russian)) || "";
 
   const formatSeason = (season: string) => {
     const seasons: Record<string, string> = { summer: "Лето", winter: "Зима", spring: "Весна", fall: "Осень" };
@@ -175,6 +178,8 @@ export default function DetailView({
                   const link = target.closest('a');
                   if (link && link.getAttribute('href')) {
                     const href = link.getAttribute('href') || "";
+                    const appLink = parseAppLink(href);
+                    if (appLink) { e.preventDefault(); onNavigate(appLink.type, appLink.id); return; }
                     const charMatch = href.match(/\/characters\/(\d+)/);
                     const animeMatch = href.match(/\/animes\/(\d+)/);
                     const mangaMatch = href.match(/\/mangas\/(\d+)/);
@@ -202,7 +207,7 @@ export default function DetailView({
             <h3 className="detail-section-title">Персонажи</h3>
             <HorizontalScroll className="detail-characters">
               {data.character_roles.slice(0, 30).map((role: any) => (
-                <PersonCard key={role.id} id={role.character.id} name={role.character.name} russian={role.character.russian} poster_url={role.character.poster_url} role={role.roles_ru?.[0] || role.roles_en?.[0]} onClick={() => onNavigate("characters", role.character.id)} />
+                <PersonCard key={role.id} id={role.character.id} name={role.character.name} russian={role.character.russian} displayTitle={role.character.display_title} poster_url={role.character.poster_url} role={role.roles_ru?.[0] || role.roles_en?.[0]} onClick={() => onNavigate("characters", role.character.id)} />
               ))}
             </HorizontalScroll>
           </div>
@@ -213,7 +218,7 @@ export default function DetailView({
             <h3 className="detail-section-title">Люди</h3>
             <HorizontalScroll className="detail-people">
               {data.person_roles.slice(0, 30).map((role: any) => (
-                <PersonCard key={role.id} id={role.person.id} name={role.person.name} russian={role.person.russian} poster_url={role.person.poster_url} role={role.roles_ru?.[0] || role.roles_en?.[0]} onClick={() => role.person.url && openUrl(role.person.url)} />
+                <PersonCard key={role.id} id={role.person.id} name={role.person.name} russian={role.person.russian} displayTitle={role.person.display_title} poster_url={role.person.poster_url} role={role.roles_ru?.[0] || role.roles_en?.[0]} onClick={() => role.person.url && openUrl(role.person.url)} />
               ))}
             </HorizontalScroll>
           </div>
@@ -239,6 +244,7 @@ export default function DetailView({
                       <RelatedCard 
                         key={rel.id} 
                         item={item as any} 
+                        displayTitle={rel.display_title}
                         type={rel.anime ? "anime" : "manga"} 
                         relation={rel.relation_kind} 
                         onClick={() => onNavigate(rel.anime ? "anime" : "manga", item.id!)} 
@@ -255,6 +261,7 @@ export default function DetailView({
                       <RelatedCard 
                         key={rel.id} 
                         item={item as any} 
+                        displayTitle={rel.display_title}
                         type={rel.anime ? "anime" : "manga"} 
                         relation={rel.relation_kind} 
                         onClick={() => onNavigate(rel.anime ? "anime" : "manga", item.id!)} 
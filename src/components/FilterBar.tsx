@@ -1,7 +1,7 @@
 import React from 'react';
 import { LoadingSpinner } from './common/LoadingSpinner';
 import { ErrorIcon, EmptyIcon } from './icons';
-import { Genre, Studio, Publisher, ContentType } from '../types';
+import { Genre, Studio, Publisher, ContentType, SearchHistoryEntry } from '../types';
 
 // Reuse existing SearchIcon if available, or define here
 const SearchIcon = () => (
@@ -16,8 +16,9 @@ interface FilterBarProps {
   contentType: ContentType;
   isSearching: boolean;
   showHistory: boolean;
-  searchHistory: string[];
-  setSearchHistory: (history: string[]) => void;
+  searchHistory: SearchHistoryEntry[];
+  clearHistory: () => void;
+  removeFromHistory: (id: number) => void;
   handleSearchChange: (e: React.ChangeEvent<HTMLInputElement>) => void;
   handleSearchKeyDown: (e: React.KeyboardEvent<HTMLInputElement>) => void;
   handleSearchFocus: () => void;
@@ -56,7 +57,7 @@ interface FilterBarProps {
 }
 
 export const FilterBar: React.FC<FilterBarProps> = ({
-  searchQuery, setSearchQuery, contentType, isSearching, showHistory, searchHistory, setSearchHistory,
+  searchQuery, setSearchQuery, contentType, isSearching, showHistory, searchHistory, clearHistory, removeFromHistory,
   handleSearchChange, handleSearchKeyDown, handleSearchFocus, handleHistorySelect, searchInputRef,
   showFilters, setShowFilters, kindFilter, handleKindChange, statusFilter, handleStatusChange,
   studioFilter, studioInput, handleStudioChange, handleStudioKeyDown, isSearchingStudios, showStudioSuggestions,
@@ -109,23 +110,33 @@ export const FilterBar: React.FC<FilterBarProps> = ({
                 className="clear-history-btn"
                 onClick={(e) => {
                   e.stopPropagation();
-                  setSearchHistory([]);
-                  localStorage.removeItem("shikimore_search_history");
+                  clearHistory();
                 }}
                 type="button"
               >
                 Очистить всё
               </button>
             </div>
-            {searchHistory.map((query, idx) => (
+            {searchHistory.map((entry) => (
               <button
-                key={idx}
+                key={entry.id}
                 className="search-history-item"
-                onClick={() => handleHistorySelect(query)}
+                onClick={() => handleHistorySelect(entry.query)}
                 type="button"
               >
                 <SearchIcon />
-                {query}
+                {entry.query}
+                <span
+                  className="search-history-remove"
+                  role="button"
+                  aria-label="Удалить из истории"
+                  onClick={(e) => {
+                    e.stopPropagation();
+                    removeFromHistory(entry.id);
+                  }}
+                >
+                  ×
+                </span>
               </button>
             ))}
           </div>
@@ -277,12 +288,6 @@ export const FilterBar: React.FC<FilterBarProps> = ({
               <div className="letters-selector">
                 {Array.from(new Set(
                   allGenres
-                    .filter(g => {
-                      const kind = g.kind?.toLowerCase();
-                      if (kind === "genre") return true;
-                      const target = contentType === "anime" ? "anime" : "manga";
-                      return kind === target;
-                    })
                     .map(g => (g.russian || g.name)[0].toUpperCase())
                 )).sort().map(letter => (
                   <button
@@ -311,12 +316,7 @@ export const FilterBar: React.FC<FilterBarProps> = ({
                         {(() => {
                           const selectedGenreIds = genreFilter.split(",");
                           return allGenres
-                            .filter(g => {
-                              const kind = g.kind?.toLowerCase();
-                              const target = contentType === "anime" ? "anime" : "manga";
-                              const matchKind = kind === "genre" || kind === target;
-                              return matchKind && (g.russian || g.name)[0].toUpperCase() === letter;
-                            })
+                            .filter(g => (g.russian || g.name)[0].toUpperCase() === letter)
                             .sort((a, b) => (a.russian || a.name).localeCompare(b.russian || b.name))
                             .map(g => {
                               const genreIds = (g as any).ids || [g.id];
@@ -6,7 +6,8 @@ import {
   Genre, 
   Studio, 
   Publisher,
-  SortOption
+  SortOption,
+  SearchHistoryEntry
 } from '../types';
 import { FilterBar } from './FilterBar';
 import { ContentCard } from './ContentCard';
@@ -24,13 +25,15 @@ interface MainScreenProps {
   hasMore: boolean;
   onRetry: () => void;
   onItemClick: (item: ContentItem) => void;
+  onItemHover?: (item: ContentItem) => void;
   settings: AppSettings | null;
   
   // Search & Filter Props
   searchQuery: string;
   setSearchQuery: (q: string) => void;
-  searchHistory: string[];
-  setSearchHistory: (h: string[]) => void;
+  searchHistory: SearchHistoryEntry[];
+  clearHistory: () => void;
+  removeFromHistory: (id: number) => void;
   showHistory: boolean;
   setShowHistory: (s: boolean) => void;
   isSearching: boolean;
@@ -93,7 +96,8 @@ export const MainScreen: React.FC<MainScreenProps> = (props) => {
         isSearching={props.isSearching}
         showHistory={props.showHistory}
         searchHistory={props.searchHistory}
-        setSearchHistory={props.setSearchHistory}
+        clearHistory={props.clearHistory}
+        removeFromHistory={props.removeFromHistory}
         handleSearchChange={props.handleSearchChange}
         handleSearchKeyDown={props.handleSearchKeyDown}
         handleSearchFocus={props.handleSearchFocus}
@@ -210,6 +214,7 @@ export const MainScreen: React.FC<MainScreenProps> = (props) => {
                 settings={props.settings}
                 cardColor={props.cardColors[item.id]}
                 onItemClick={props.onItemClick}
+                onItemHover={props.onItemHover}
                 onCopyLink={props.onCopyLink}
                 onImageLoad={props.onImageLoad}
                 innerRef={(el) => {
@@ -5,6 +5,8 @@ interface PersonCardProps {
   id: number;
   name: string;
   russian?: string;
+  /** Имя, выбранное бэкендом по настройке preferred_language. */
+  displayTitle?: string;
   poster_url?: string;
   role?: string;
   onClick: () => void;
@@ -15,12 +17,13 @@ export const PersonCard: React.FC<PersonCardProps> = ({
   id,
   name,
   russian,
+  displayTitle,
   poster_url,
   role,
   onClick,
   className = '',
 }) => {
-  const displayName = russian || name;
+  const displayName = displayTitle || russian || name;
 
   return (
     <div 
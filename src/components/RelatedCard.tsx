@@ -16,7 +16,10 @@ interface RelatedCardProps {
     kind?: string;
     aired_on?: string;
     title?: string;
+    display_title?: string;
   };
+  /** Название связи, выбранное бэкендом; важнее названия в `item`. */
+  displayTitle?: string;
   type: 'anime' | 'manga';
   relation?: string;
   relationRussian?: string;
@@ -27,6 +30,7 @@ interface RelatedCardProps {
 
 export const RelatedCard: React.FC<RelatedCardProps> = ({
   item,
+  displayTitle: preferredTitle,
   type,
   relation,
   relationRussian,
@@ -37,7 +41,7 @@ export const RelatedCard: React.FC<RelatedCardProps> = ({
   const isAnime = type === 'anime';
   const russianTitle = item.russian;
   const originalTitle = item.title || item.name;
-  const displayTitle = (russianTitle || originalTitle) || `${isAnime ? 'Аниме' : 'Манга'} #${item.id}`;
+  const displayTitle = (preferredTitle || item.display_title || russianTitle || originalTitle) || `${isAnime ? 'Аниме' : 'Манга'} #${item.id}`;
   
   const posterUrl = item.poster_url || (item.image?.preview ? (item.image.preview.startsWith('http') ? item.image.preview : `https://shikimori.one${item.image.preview}`) : undefined);
 
@@ -10,6 +10,8 @@ interface SettingsViewProps {
 
 const SettingsView = ({ onClose, onSettingsChange }: SettingsViewProps) => {
   const [settings, setSettings] = useState<AppSettings | null>(null);
+  const [profiles, setProfiles] = useState<string[]>([]);
+  const [profileName, setProfileName] = useState("");
 
   useEffect(() => {
     const init = async () => {
@@ -18,8 +20,30 @@ const SettingsView = ({ onClose, onSettingsChange }: SettingsViewProps) => {
       setSettings({ ...s, autostart: autostartEnabled });
     };
     init();
+    invoke<string[]>("list_profiles").then(setProfiles).catch(() => {});
   }, []);
 
+  const saveProfile = async () => {
+    const name = profileName.trim();
+    if (!name) return;
+    try {
+      await invoke("save_profile", { name });
+      setProfiles(await invoke<string[]>("list_profiles"));
+      setProfileName("");
+    } catch (err) {
+      console.error("Failed to save profile:", err);
+    }
+  };
+
+  // Бэкенд сам применяет автозапуск и рассылает settings-changed
+  const switchProfile = async (name: string) => {
+    try {
+      setSettings(await invoke<AppSettings>("switch_profile", { name }));
+    } catch (err) {
+      console.error("Failed to switch profile:", err);
+    }
+  };
+
   const save = async (newSettings: AppSettings) => {
     setSettings(newSettings);
     // Оптимистичное обновление UI
@@ -81,10 +105,26 @@ const SettingsView = ({ onClose, onSettingsChange }: SettingsViewProps) => {
               onChange={(e) => save({ ...settings, preferred_language: e.target.value })}
             >
               <option value="russian">Русский</option>
+              <option value="english">Английский</option>
               <option value="original">Оригинальный</option>
             </select>
           </div>
 
+          <div className="settings-group">
+            <div className="settings-label-group">
+              <label>Язык сообщений</label>
+              <p className="settings-hint">Язык ошибок и системных сообщений приложения</p>
+            </div>
+            <select 
+              className="kind-filter"
+              value={settings.ui_language ?? "ru"} 
+              onChange={(e) => save({ ...settings, ui_language: e.target.value })}
+            >
+              <option value="ru">Русский</option>
+              <option value="en">English</option>
+            </select>
+          </div>
+
           <div className="settings-group">
             <div className="settings-label-group">
               <label>Вид списка</label>
@@ -115,6 +155,37 @@ const SettingsView = ({ onClose, onSettingsChange }: SettingsViewProps) => {
             </label>
           </div>
 
+          <div className="settings-group">
+            <div className="settings-label-group">
+              <label>История поиска</label>
+              <p className="settings-hint">Запоминать запросы и предлагать их при вводе</p>
+            </div>
+            <label className="switch">
+              <input 
+                type="checkbox" 
+                checked={settings.search_history_enabled} 
+                onChange={(e) => save({ ...settings, search_history_enabled: e.target.checked })}
+              />
+              <span className="slider round"></span>
+            </label>
+          </div>
+
+          <div className="settings-group">
+            <div className="settings-label-group">
+              <label>Спойлеры</label>
+              <p className="settings-hint">Как показывать спойлеры в описаниях</p>
+            </div>
+            <select 
+              className="kind-filter"
+              value={settings.spoiler_mode} 
+              onChange={(e) => save({ ...settings, spoiler_mode: e.target.value as AppSettings['spoiler_mode'] })}
+            >
+              <option value="blur">Скрывать до клика</option>
+              <option value="show">Показывать</option>
+              <option value="strip">Вырезать</option>
+            </select>
+          </div>
+
           <div className="settings-group">
             <div className="settings-label-group">
               <label>Сворачивать в трей</label>
@@ -160,6 +231,36 @@ const SettingsView = ({ onClose, onSettingsChange }: SettingsViewProps) => {
               <span className="color-value">{settings.accent_color.toUpperCase()}</span>
             </div>
           </div>
+
+          <div className="settings-group">
+            <div className="settings-label-group">
+              <label>Профили</label>
+              <p className="settings-hint">Сохранить текущие настройки под именем или переключиться на сохранённые</p>
+            </div>
+            <div className="color-input-wrapper">
+              {profiles.length > 0 && (
+                <select 
+                  className="kind-filter"
+                  value=""
+                  onChange={(e) => e.target.value && switchProfile(e.target.value)}
+                >
+                  <option value="">Выбрать…</option>
+                  {profiles.map((name) => (
+                    <option key={name} value={name}>{name}</option>
+                  ))}
+                </select>
+              )}
+              <input 
+                type="text"
+                className="search-input"
+                placeholder="Имя профиля"
+                value={profileName}
+                onChange={(e) => setProfileName(e.target.value)}
+                onKeyDown={(e) => e.key === "Enter" && saveProfile()}
+              />
+              <button className="retry-btn" onClick={saveProfile} disabled={!profileName.trim()}>Сохранить</button>
+            </div>
+          </div>
         </div>
 
         <div className="settings-footer">
@@ -1,4 +1,5 @@
 import { useState, useEffect, useCallback } from 'react';
+import { listen } from '@tauri-apps/api/event';
 import { api } from '../services/api';
 import { AppSettings } from '../types';
 import { adjustColor } from '../utils/formatters';
@@ -43,6 +44,17 @@ export function useAppSettings() {
     initSettings();
   }, [applySettings]);
 
+  // Настройки, сохранённые в другом окне или другим кодом
+  useEffect(() => {
+    const unlisten = listen<AppSettings>('settings-changed', (event) => {
+      setSettings(event.payload);
+      applySettings(event.payload);
+    }).catch(() => undefined);
+    return () => {
+      unlisten.then(fn => fn?.());
+    };
+  }, [applySettings]);
+
   const updateSettings = async (newSettings: AppSettings) => {
     try {
       await api.updateSettings(newSettings);
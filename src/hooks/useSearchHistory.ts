@@ -1,32 +1,74 @@
-import { useState, useEffect } from 'react';
+import { useState, useEffect, useCallback } from 'react';
+import { api } from '../services/api';
+import { ClickedResult, ContentType, SearchHistoryEntry } from '../types';
+
+const LEGACY_HISTORY_KEY = "shikimore_search_history";
 
 export function useSearchHistory() {
-  const [searchHistory, setSearchHistory] = useState<string[]>([]);
+  const [searchHistory, setSearchHistory] = useState<SearchHistoryEntry[]>([]);
 
-  useEffect(() => {
-    const history = localStorage.getItem("shikimore_search_history");
-    if (history) {
-      try {
-        setSearchHistory(JSON.parse(history));
-      } catch {
-        // ignore
-      }
+  const refresh = useCallback(async () => {
+    try {
+      const entries = await api.getSearchHistory(undefined, 10);
+      setSearchHistory(Array.isArray(entries) ? entries : []);
+    } catch (err) {
+      console.error("Ошибка загрузки истории поиска:", err);
     }
   }, []);
 
-  const addToHistory = (query: string) => {
+  useEffect(() => {
+    const init = async () => {
+      // Переносим историю, которая раньше хранилась в localStorage
+      const legacy = localStorage.getItem(LEGACY_HISTORY_KEY);
+      if (legacy) {
+        try {
+          const queries: string[] = JSON.parse(legacy);
+          for (const query of [...queries].reverse()) {
+            await api.recordSearch(query, "anime");
+          }
+        } catch {
+          // ignore
+        }
+        localStorage.removeItem(LEGACY_HISTORY_KEY);
+      }
+      await refresh();
+    };
+    init();
+  }, [refresh]);
+
+  const addToHistory = useCallback(async (
+    query: string,
+    contentType: ContentType,
+    filters?: Record<string, string>,
+    clicked?: ClickedResult
+  ) => {
     if (!query.trim()) return;
-    setSearchHistory(prev => {
-      const newHistory = [query, ...prev.filter(q => q !== query)].slice(0, 10);
-      localStorage.setItem("shikimore_search_history", JSON.stringify(newHistory));
-      return newHistory;
-    });
-  };
-
-  const clearHistory = () => {
+    try {
+      await api.recordSearch(query, contentType, filters, clicked);
+      await refresh();
+    } catch (err) {
+      console.error("Ошибка сохранения истории поиска:", err);
+    }
+  }, [refresh]);
+
+  const removeFromHistory = useCallback(async (id: number) => {
+    setSearchHistory(prev => prev.filter(e => e.id !== id));
+    try {
+      await api.deleteSearchHistoryEntry(id);
+    } catch (err) {
+      console.error("Ошибка удаления из истории поиска:", err);
+    }
+    await refresh();
+  }, [refresh]);
+
+  const clearHistory = useCallback(async () => {
     setSearchHistory([]);
-    localStorage.removeItem("shikimore_search_history");
-  };
+    try {
+      await api.clearSearchHistory();
+    } catch (err) {
+      console.error("Ошибка очистки истории поиска:", err);
+    }
+  }, []);
 
-  return { searchHistory, setSearchHistory, addToHistory, clearHistory };
+  return { searchHistory, addToHistory, removeFromHistory, clearHistory };
 }
@@ -59,7 +59,7 @@ export function useShikimoriApi() {
       } else if (contentType === "characters") {
         result = await api.searchCharacters({ query, page, limit });
       } else {
-        result = await api.searchPeople({ query, limit });
+        result = await api.searchPeople({ query, page, limit });
       }
 
       // Если за время запроса параметры поиска изменились, игнорируем результат
@@ -72,7 +72,7 @@ export function useShikimoriApi() {
         setCurrentPage(page);
       }
       
-      setHasMore(result.items.length === limit);
+      setHasMore(result.has_next_page ?? result.items.length === limit);
     } catch (err) {
       const apiErr = err as ApiError;
       setError(apiErr.message || "Произошла ошибка при загрузке данных");
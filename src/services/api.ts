@@ -1,4 +1,4 @@
-import { invoke } from "@tauri-apps/api/core";
+import { invoke, Channel } from "@tauri-apps/api/core";
 import { 
   Anime, 
   Manga, 
@@ -8,21 +8,63 @@ import {
   MangaDetail, 
   CharacterDetail, 
   SearchResult, 
-  Genre, 
+  ByIdEntry,
+  DetailEvent,
+  PrefetchEntity,
+  PrefetchPriority,
+  GenreCatalog, 
   Studio, 
   Publisher,
   AppSettings,
+  UiLanguage,
+  FilterOptions,
+  SearchAllEvent,
+  SearchHistoryEntry,
+  SearchSuggestion,
+  ClickedResult,
+  SavedSearch,
+  SavedSearchParams,
+  SearchAllHit,
   ContentType,
   SortOption
 } from "../types";
 
+let requestCounter = 0;
+
+/** Токен для отменяемой команды, см. `api.cancelRequest`. */
+export const newRequestToken = (prefix: string) => `${prefix}-${Date.now()}-${++requestCounter}`;
+
 export const api = {
   getSettings: () => invoke<AppSettings>("get_settings"),
   updateSettings: (settings: AppSettings) => invoke<void>("update_settings", { settings }),
+  /** Общие с бэкендом строки интерфейса; без языка — на языке из настроек. */
+  getTranslations: (language?: UiLanguage) => invoke<Record<string, string>>("get_translations", { language }),
+  exportSettings: (path: string) => invoke<void>("export_settings", { path }),
+  importSettings: (path: string) => invoke<AppSettings>("import_settings", { path }),
+  listProfiles: () => invoke<string[]>("list_profiles"),
+  saveProfile: (name: string) => invoke<void>("save_profile", { name }),
+  deleteProfile: (name: string) => invoke<void>("delete_profile", { name }),
+  switchProfile: (name: string) => invoke<AppSettings>("switch_profile", { name }),
+  
+  recordSearch: (query: string, contentType: ContentType, filters?: Record<string, string>, clicked?: ClickedResult) =>
+    invoke<void>("record_search", { query, contentType, filters, clicked }),
+  getSearchHistory: (contentType?: ContentType, limit?: number) =>
+    invoke<SearchHistoryEntry[]>("get_search_history", { contentType, limit }),
+  deleteSearchHistoryEntry: (id: number) => invoke<void>("delete_search_history_entry", { id }),
+  clearSearchHistory: () => invoke<void>("clear_search_history"),
+  getSearchSuggestions: (query: string, contentType?: ContentType, limit?: number) =>
+    invoke<SearchSuggestion[]>("get_search_suggestions", { query, contentType, limit }),
+  
+  createSavedSearch: (name: string, params: SavedSearchParams) =>
+    invoke<SavedSearch>("create_saved_search", { name, params }),
+  getSavedSearches: () => invoke<SavedSearch[]>("get_saved_searches"),
+  deleteSavedSearch: (id: number) => invoke<void>("delete_saved_search", { id }),
+  checkSavedSearches: () => invoke<void>("check_saved_searches"),
   
-  getGenres: () => invoke<Genre[]>("get_genres"),
-  searchStudios: (query: string) => invoke<Studio[]>("search_studios", { query }),
-  searchPublishers: (query: string) => invoke<Publisher[]>("search_publishers", { query }),
+  getGenres: () => invoke<GenreCatalog>("get_genres"),
+  getFilterOptions: () => invoke<FilterOptions>("get_filter_options"),
+  searchStudios: (query: string, requestToken?: string) => invoke<Studio[]>("search_studios", { query, requestToken }),
+  searchPublishers: (query: string, requestToken?: string) => invoke<Publisher[]>("search_publishers", { query, requestToken }),
   
   getAccentColor: (url: string) => invoke<string>("get_accent_color", { url }),
   
@@ -32,9 +74,21 @@ export const api = {
     limit?: number;
     kind?: string;
     status?: string;
+    season?: string;
+    rating?: string;
     genre?: string;
+    genreInclude?: number[];
+    genreExclude?: number[];
     studio?: string;
     order?: SortOption;
+    scoreMin?: number;
+    yearFrom?: number;
+    yearTo?: number;
+    episodesMin?: number;
+    episodesMax?: number;
+    durationMin?: number;
+    durationMax?: number;
+    requestToken?: string;
   }) => invoke<SearchResult<Anime>>("search_anime", params),
   
   searchManga: (params: {
@@ -44,8 +98,18 @@ export const api = {
     kind?: string;
     status?: string;
     genre?: string;
+    genreInclude?: number[];
+    genreExclude?: number[];
     publisher?: string;
     order?: SortOption;
+    scoreMin?: number;
+    yearFrom?: number;
+    yearTo?: number;
+    volumesMin?: number;
+    volumesMax?: number;
+    chaptersMin?: number;
+    chaptersMax?: number;
+    requestToken?: string;
   }) => invoke<SearchResult<Manga>>("search_manga", params),
   
   searchCharacters: (params: {
@@ -53,14 +117,52 @@ export const api = {
     page?: number;
     limit?: number;
     ids?: string[];
+    isAnime?: boolean;
+    isManga?: boolean;
+    isRanobe?: boolean;
+    animeId?: number;
+    role?: string;
+    requestToken?: string;
   }) => invoke<SearchResult<Character>>("search_characters", params),
   
   searchPeople: (params: {
     query: string;
+    page?: number;
     limit?: number;
+    isSeyu?: boolean;
+    isMangaka?: boolean;
+    isProducer?: boolean;
+    requestToken?: string;
   }) => invoke<SearchResult<Person>>("search_people", params),
   
-  getAnimeById: (id: number) => invoke<AnimeDetail>("get_anime_by_id", { id }),
-  getMangaById: (id: number) => invoke<MangaDetail>("get_manga_by_id", { id }),
-  getCharacterDetails: (id: number) => invoke<CharacterDetail>("get_character_details", { id }),
+  searchAll: (query: string, onEvent: (event: SearchAllEvent) => void, limit?: number) => {
+    const channel = new Channel<SearchAllEvent>();
+    channel.onmessage = onEvent;
+    return invoke<SearchAllHit[]>("search_all", { query, limit, onEvent: channel });
+  },
+  
+  getAnimesByIds: (ids: number[]) => invoke<ByIdEntry<Anime>[]>("get_animes_by_ids", { ids }),
+  getMangasByIds: (ids: number[]) => invoke<ByIdEntry<Manga>[]>("get_mangas_by_ids", { ids }),
+  getCharactersByIds: (ids: number[]) => invoke<ByIdEntry<Character>[]>("get_characters_by_ids", { ids }),
+  
+  getAnimeById: (id: number, collapseSpoilers?: boolean, requestToken?: string) =>
+    invoke<AnimeDetail>("get_anime_by_id", { id, collapseSpoilers, requestToken }),
+  getMangaById: (id: number, collapseSpoilers?: boolean, requestToken?: string) =>
+    invoke<MangaDetail>("get_manga_by_id", { id, collapseSpoilers, requestToken }),
+  streamAnimeDetail: (id: number, onEvent: (event: DetailEvent<AnimeDetail>) => void, collapseSpoilers?: boolean, requestToken?: string) => {
+    const channel = new Channel<DetailEvent<AnimeDetail>>();
+    channel.onmessage = onEvent;
+    return invoke<void>("stream_anime_detail", { id, collapseSpoilers, requestToken, onEvent: channel });
+  },
+  streamMangaDetail: (id: number, onEvent: (event: DetailEvent<MangaDetail>) => void, collapseSpoilers?: boolean, requestToken?: string) => {
+    const channel = new Channel<DetailEvent<MangaDetail>>();
+    channel.onmessage = onEvent;
+    return invoke<void>("stream_manga_detail", { id, collapseSpoilers, requestToken, onEvent: channel });
+  },
+  getCharacterDetails: (id: number, collapseSpoilers?: boolean, requestToken?: string) =>
+    invoke<CharacterDetail>("get_character_details", { id, collapseSpoilers, requestToken }),
+  
+  prefetch: (entity: PrefetchEntity, ids: number[], priority?: PrefetchPriority) =>
+    invoke<void>("prefetch", { entity, ids, priority }),
+  cancelRequest: (token: string) => invoke<boolean>("cancel_request", { token }),
 };
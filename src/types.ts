@@ -10,6 +10,18 @@ export interface Genre {
   name: string;
   russian?: string;
   kind?: string;
+  label?: string;
+}
+
+export interface GenreGroups {
+  genres: Genre[];
+  themes: Genre[];
+  demographics: Genre[];
+}
+
+export interface GenreCatalog {
+  anime: GenreGroups;
+  manga: GenreGroups;
 }
 
 export interface Studio {
@@ -31,7 +43,13 @@ export interface ExternalLink {
   updated_at?: string;
 }
 
-export interface Person {
+/** Названия, выбранные бэкендом по настройке preferred_language. */
+export interface Titles {
+  display_title?: string;
+  secondary_title?: string;
+}
+
+export interface Person extends Titles {
   id: number;
   name: string;
   russian?: string;
@@ -50,7 +68,7 @@ export interface PersonRole {
   person: Person;
 }
 
-export interface Character {
+export interface Character extends Titles {
   id: number;
   name: string;
   russian?: string;
@@ -91,7 +109,7 @@ export interface RelatedManga {
   image?: Poster;
 }
 
-export interface Related {
+export interface Related extends Titles {
   id: number;
   anime?: RelatedAnime;
   manga?: RelatedManga;
@@ -125,7 +143,19 @@ export interface StatusStat {
   count: number;
 }
 
-export interface Anime {
+export type EntityKind = "anime" | "manga" | "ranobe" | "character" | "person";
+
+export type RichNode =
+  | { type: "text"; text: string }
+  | { type: "line_break" }
+  | { type: "format"; style: "bold" | "italic" | "underline" | "strike"; children: RichNode[] }
+  | { type: "entity_link"; entity: EntityKind; id: number; text?: string }
+  | { type: "url"; href: string; children: RichNode[] }
+  | { type: "spoiler"; label?: string; children: RichNode[] }
+  | { type: "quote"; author?: string; children: RichNode[] }
+  | { type: "list"; items: RichNode[][] };
+
+export interface Anime extends Titles {
   id: number;
   title: string;
   russian?: string;
@@ -138,7 +168,7 @@ export interface Anime {
   episodes_aired?: number;
 }
 
-export interface Manga {
+export interface Manga extends Titles {
   id: number;
   title: string;
   russian?: string;
@@ -151,7 +181,7 @@ export interface Manga {
   chapters?: number;
 }
 
-export interface AnimeDetail {
+export interface AnimeDetail extends Titles {
   id: number;
   mal_id?: number;
   title: string;
@@ -164,6 +194,7 @@ export interface AnimeDetail {
   poster_url?: string;
   description?: string;
   description_html?: string;
+  description_rich?: RichNode[];
   description_source?: string;
   score?: number;
   kind?: string;
@@ -192,7 +223,7 @@ export interface AnimeDetail {
   licensors?: string[];
 }
 
-export interface MangaDetail {
+export interface MangaDetail extends Titles {
   id: number;
   mal_id?: number;
   title: string;
@@ -205,6 +236,7 @@ export interface MangaDetail {
   poster_url?: string;
   description?: string;
   description_html?: string;
+  description_rich?: RichNode[];
   description_source?: string;
   score?: number;
   kind?: string;
@@ -225,7 +257,7 @@ export interface MangaDetail {
   licensors?: string[];
 }
 
-export interface CharacterDetail {
+export interface CharacterDetail extends Titles {
   id: number;
   name: string;
   russian?: string;
@@ -235,10 +267,16 @@ export interface CharacterDetail {
   poster_url?: string;
   description?: string;
   description_html?: string;
+  description_rich?: RichNode[];
   character_roles: CharacterRoleDetail[];
   seyus: Person[];
+  poster?: Poster;
+  source: DetailSource;
 }
 
+/** Откуда взяты данные: "merged" — основные поля из GraphQL, роли и сейю из REST. */
+export type DetailSource = "graphql" | "rest" | "merged";
+
 export interface CharacterRoleDetail {
   id: number;
   roles_ru: string[];
@@ -252,29 +290,199 @@ export interface SearchResult<T> {
   items: T[];
   page: number;
   limit: number;
+  has_next_page?: boolean;
+  total?: number;
+  corrected_query?: string;
+}
+
+export type DetailSection =
+  | { section: "character_roles"; data: CharacterRole[] }
+  | { section: "person_roles"; data: PersonRole[] }
+  | { section: "related"; data: Related[] }
+  | { section: "screenshots"; data: Screenshot[] }
+  | { section: "external_links"; data: ExternalLink[] };
+
+export type DetailEvent<T> =
+  | { event: "core"; data: T }
+  | { event: "section"; data: DetailSection }
+  | { event: "done" };
+
+export type PrefetchEntity = "anime" | "manga";
+export type PrefetchPriority = "visible" | "hover";
+
+export type ByIdEntry<T> =
+  | { status: "found"; id: number; item: T }
+  | { status: "missing"; id: number };
+
+export interface FilterOption {
+  value: string;
+  label: string;
+}
+
+export interface FilterOptions {
+  anime_kinds: FilterOption[];
+  manga_kinds: FilterOption[];
+  anime_statuses: FilterOption[];
+  manga_statuses: FilterOption[];
+  ratings: FilterOption[];
+  orders: FilterOption[];
+  seasons: FilterOption[];
+  character_roles: FilterOption[];
 }
 
 export type ContentType = "anime" | "manga" | "characters" | "people";
 export type SortOption = "relevance" | "score" | "title";
 
+export type SearchEntity = "anime" | "manga" | "character" | "person" | "studio" | "publisher";
+
+export type SearchAllItem =
+  | { entity: "anime"; item: Anime }
+  | { entity: "manga"; item: Manga }
+  | { entity: "character"; item: Character }
+  | { entity: "person"; item: Person }
+  | { entity: "studio"; item: Studio }
+  | { entity: "publisher"; item: Publisher };
+
+export type SearchAllHit = SearchAllItem & { rank: number };
+
+export type SearchAllEvent =
+  | { event: "partial"; data: { entity: SearchEntity; hits: SearchAllHit[] } }
+  | { event: "failed"; data: { entity: SearchEntity; error: ApiError } };
+
 export interface ApiError {
-  kind: "validation" | "http" | "graphql" | "rate_limit" | "api" | "serialization" | "not_found";
+  kind: "validation" | "http" | "graphql" | "rate_limit" | "api" | "serialization" | "not_found" | "internal" | "cancelled";
+  /** Стабильный код причины, например "anime_not_found" или "rate_limited". */
+  code: ErrorCode;
+  /** Текст на языке из настройки ui_language. */
   message: string;
+  status?: number;
+  retryable: boolean;
   retry_after?: number;
+  /** Исходные ошибки для отладки. */
+  source: string[];
 }
 
+export type ErrorCode =
+  | "invalid_parameter"
+  | "network"
+  | "graphql_error"
+  | "rate_limited"
+  | "http_status"
+  | "invalid_response"
+  | "anime_not_found"
+  | "manga_not_found"
+  | "character_not_found"
+  | "profile_not_found"
+  | "image_fetch"
+  | "image_decode"
+  | "storage"
+  | "task_failed"
+  | "channel"
+  | "cancelled";
+
 export interface Toast {
   id: string;
   message: string;
   type: "success" | "error" | "info";
 }
 
+export type UiLanguage = 'ru' | 'en';
+
 export interface AppSettings {
+  /** Версия формата файла настроек; заполняет бэкенд. */
+  version?: number;
   theme: 'dark' | 'light' | 'system';
   nsfw: boolean;
   accent_color: string;
-  preferred_language: 'russian' | 'original';
+  preferred_language: 'russian' | 'english' | 'original';
   view_mode: 'grid' | 'list';
   autostart: boolean;
   tray: boolean;
+  spoiler_mode: 'show' | 'blur' | 'strip';
+  search_history_enabled: boolean;
+  ui_language?: UiLanguage;
+}
+
+export interface ClickedResult {
+  id: number;
+  title: string;
+}
+
+export interface SearchHistoryEntry {
+  id: number;
+  query: string;
+  content_type: ContentType;
+  filters: Record<string, string>;
+  clicked?: ClickedResult;
+  count: number;
+  first_used: number;
+  last_used: number;
+}
+
+export interface SavedAnimeSearch {
+  type: "anime";
+  query?: string;
+  kind?: string;
+  status?: string;
+  season?: string;
+  rating?: string;
+  genre?: string;
+  genre_include?: number[];
+  genre_exclude?: number[];
+  studio?: string;
+  order?: string;
+  score_min?: number;
+  year_from?: number;
+  year_to?: number;
+  episodes_min?: number;
+  episodes_max?: number;
+  duration_min?: number;
+  duration_max?: number;
+}
+
+export interface SavedMangaSearch {
+  type: "manga";
+  query?: string;
+  kind?: string;
+  status?: string;
+  genre?: string;
+  genre_include?: number[];
+  genre_exclude?: number[];
+  publisher?: string;
+  order?: string;
+  score_min?: number;
+  year_from?: number;
+  year_to?: number;
+  volumes_min?: number;
+  volumes_max?: number;
+  chapters_min?: number;
+  chapters_max?: number;
+}
+
+export type SavedSearchParams = SavedAnimeSearch | SavedMangaSearch;
+
+export interface SavedSearch {
+  id: number;
+  name: string;
+  params: SavedSearchParams;
+  known_ids: number[];
+  created_at: number;
+  last_checked?: number;
+}
+
+export interface SavedSearchMatch {
+  id: number;
+  title: string;
+  russian?: string;
+  poster_url?: string;
 }
+
+export interface SavedSearchUpdate {
+  search_id: number;
+  name: string;
+  new_items: SavedSearchMatch[];
+}
+
+export type SearchSuggestion =
+  | { source: "history"; id: number; query: string; content_type: ContentType }
+  | { source: "anime"; id: number; title: string; russian?: string };
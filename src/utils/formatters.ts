@@ -160,3 +160,19 @@ export const formatDate = (date?: string | { year?: number; month?: number; day?
 
   return null;
 };
+
+/**
+ * Разбирает ссылку навигации внутри приложения вида shikimore://anime/5
+ */
+export const parseAppLink = (href: string): { type: "anime" | "manga" | "characters" | "people"; id: number } | null => {
+  const match = href.match(/^shikimore:\/\/(anime|manga|ranobe|character|person)\/(\d+)/);
+  if (!match) return null;
+  const types = {
+    anime: "anime",
+    manga: "manga",
+    ranobe: "manga",
+    character: "characters",
+    person: "people",
+  } as const;
+  return { type: types[match[1] as keyof typeof types], id: parseInt(match[2]) };
+};